@@ -0,0 +1,28 @@
+//! Analyzes a chain of additions of growing length, the worst case for the
+//! left associative operators. Run with `cargo bench --bench analyzer`
+use std::{hint::black_box, time::Instant};
+
+use analizador_sin::app::analyzer::sintactic::Analyzer;
+
+const TERM: &str = " + x * 2";
+const RUNS: usize = 5;
+
+fn main() {
+    println!("{:>10} {:>10} {:>10}", "terms", "ms", "µs/term");
+    for doubling in 0..5 {
+        let terms = 500 << doubling;
+        let input = format!("1{}", TERM.repeat(terms));
+        let mut best = f64::MAX;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            black_box(Analyzer::new(black_box(&input), &Default::default()).analyze());
+            best = best.min(start.elapsed().as_secs_f64());
+        }
+        println!(
+            "{:>10} {:>10.2} {:>10.2}",
+            terms,
+            best * 1e3,
+            best * 1e6 / terms as f64
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Tokenizes inputs of growing size, the lexer is linear when the time per
+//! byte stays about the same as the input doubles. Run with `cargo bench`
+use std::{hint::black_box, time::Instant};
+
+use analizador_sin::app::analyzer::lexic::tokenize;
+
+const STATEMENT: &str = "total_2 = (3.25e2 + x) * 42 <= limit && !done ? 1 : 0;\n";
+const RUNS: usize = 5;
+
+fn main() {
+    println!(
+        "{:>10} {:>10} {:>10} {:>10}",
+        "bytes", "tokens", "ms", "ns/byte"
+    );
+    for doubling in 0..6 {
+        let input = STATEMENT.repeat(1000 << doubling);
+        let mut best = f64::MAX;
+        let mut tokens = 0;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            tokens = black_box(tokenize(black_box(&input))).len();
+            best = best.min(start.elapsed().as_secs_f64());
+        }
+        println!(
+            "{:>10} {:>10} {:>10.2} {:>10.2}",
+            input.len(),
+            tokens,
+            best * 1e3,
+            best * 1e9 / input.len() as f64
+        );
+    }
+}
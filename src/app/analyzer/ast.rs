@@ -0,0 +1,295 @@
+use std::rc::Rc;
+
+use super::{
+    lexic::{type_to_string, Span, Token, TokenType},
+    types::{Type, Value},
+};
+use crate::app::tree::{Attributes, TreeItem};
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Num {
+        token: Token<'static>,
+        value: Value,
+    },
+    Var(Token<'static>),
+    Unary {
+        operator: Token<'static>,
+        operand: Box<Expr>,
+    },
+    Binary {
+        operator: Token<'static>,
+        left: Box<Expr>,
+        right: Box<Expr>,
+        /// Kept in the node, a long chain would be walked down to its first
+        /// operand for every operator
+        span: Span,
+    },
+    Call {
+        name: Token<'static>,
+        args: Vec<Expr>,
+        close: Token<'static>,
+    },
+    Conditional {
+        keyword: Token<'static>,
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+        value_type: Type,
+    },
+    Assign {
+        name: Token<'static>,
+        value_type: Type,
+        value: Box<Expr>,
+    },
+    Declaration {
+        type_name: Token<'static>,
+        declared_type: Type,
+        name: Token<'static>,
+        value: Option<Box<Expr>>,
+    },
+    Function {
+        name: Token<'static>,
+        params: Vec<String>,
+        /// Shared with the definition the calls evaluate
+        body: Rc<Expr>,
+    },
+    Program(Vec<Expr>),
+    /// Placeholder for an operand that could not be parsed
+    Error(Token<'static>),
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Expr::Program(Vec::new())
+    }
+}
+
+impl Expr {
+    pub fn binary(operator: Token<'static>, left: Expr, right: Expr) -> Self {
+        Expr::Binary {
+            span: (left.span().0, right.span().1),
+            operator,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /// Byte offsets of the expression in the input
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Num { token, .. } | Expr::Var(token) | Expr::Error(token) => token.span,
+            Expr::Unary { operator, operand } => (operator.span.0, operand.span().1),
+            Expr::Binary { span, .. } => *span,
+            Expr::Call { name, close, .. } => (name.span.0, close.span.1),
+            Expr::Conditional {
+                keyword,
+                condition,
+                otherwise,
+                ..
+            } => match keyword.token_type {
+                TokenType::If => (keyword.span.0, otherwise.span().1),
+                _ => (condition.span().0, otherwise.span().1),
+            },
+            Expr::Assign { name, value, .. } => (name.span.0, value.span().1),
+            Expr::Declaration {
+                type_name,
+                name,
+                value,
+                ..
+            } => match value {
+                Some(value) => (type_name.span.0, value.span().1),
+                None => (type_name.span.0, name.span.1),
+            },
+            Expr::Function { name, body, .. } => (name.span.0, body.span().1),
+            Expr::Program(stmts) => match (stmts.first(), stmts.last()) {
+                (Some(first), Some(last)) => (first.span().0, last.span().1),
+                _ => (0, 0),
+            },
+        }
+    }
+
+    /// Prefix notation, a statement per line. Written from a stack of the
+    /// nodes left to write, a chain of left associative operators nests one
+    /// operator in another as deep as the chain is long
+    pub fn prefix(&self) -> String {
+        if let Expr::Program(stmts) = self {
+            let prefix: Vec<String> = stmts.iter().map(Expr::prefix).collect();
+            return prefix.join("\n");
+        }
+        let mut notation = Vec::new();
+        let mut pending = vec![self];
+        while let Some(expr) = pending.pop() {
+            notation.push(match expr {
+                Expr::Assign { name, .. } => format!("= {}", name.lexeme),
+                Expr::Declaration { value: Some(_), .. } | Expr::Function { .. } => {
+                    format!("= {}", expr.label())
+                }
+                _ => expr.label(),
+            });
+            pending.extend(expr.operands().into_iter().rev());
+        }
+        notation.join(" ")
+    }
+
+    /// Tree view of the AST, statements that bind a name hang the bound value
+    /// from a `=` node. The nodes are listed breadth first and the tree is
+    /// built from the last one, so no node waits on the stack for its operands
+    pub fn tree(&self) -> TreeItem {
+        // Each node with the index of its first operand in the list
+        let mut nodes = vec![(self, 0)];
+        let mut next = 0;
+        while next < nodes.len() {
+            let expr = nodes[next].0;
+            nodes[next].1 = nodes.len();
+            nodes.extend(expr.operands().into_iter().map(|operand| (operand, 0)));
+            next += 1;
+        }
+        let mut trees: Vec<Option<TreeItem>> = Vec::new();
+        trees.resize_with(nodes.len(), || None);
+        for (index, (expr, first)) in nodes.iter().enumerate().rev() {
+            let (root, mut items) = match expr {
+                Expr::Assign { name, .. } => (String::from("="), vec![TreeItem::new(&name.lexeme)]),
+                Expr::Declaration { value: Some(_), .. } | Expr::Function { .. } => {
+                    (String::from("="), vec![TreeItem::new(&expr.label())])
+                }
+                _ => (expr.label(), Vec::new()),
+            };
+            let operands = &mut trees[*first..*first + expr.operands().len()];
+            items.extend(operands.iter_mut().filter_map(Option::take));
+            trees[index] = Some(TreeItem {
+                root,
+                items,
+                attributes: Attributes {
+                    span: Some(expr.span()),
+                    ..Default::default()
+                },
+                inherited: None,
+            });
+        }
+        trees[0].take().unwrap_or_default()
+    }
+
+    /// Direct subexpressions, in input order
+    fn operands(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Num { .. } | Expr::Var(_) | Expr::Error(_) => Vec::new(),
+            Expr::Declaration { value: None, .. } => Vec::new(),
+            Expr::Unary { operand, .. } => vec![operand],
+            Expr::Binary { left, right, .. } => vec![left, right],
+            Expr::Call { args, .. } | Expr::Program(args) => args.iter().collect(),
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => vec![condition, then, otherwise],
+            Expr::Assign { value, .. }
+            | Expr::Declaration {
+                value: Some(value), ..
+            } => vec![value],
+            Expr::Function { body, .. } => vec![body],
+        }
+    }
+
+    /// Moves the direct subexpressions out of the node, leaving it without
+    /// operands
+    fn take_operands(&mut self) -> Vec<Expr> {
+        match self {
+            Expr::Num { .. } | Expr::Var(_) | Expr::Error(_) => Vec::new(),
+            Expr::Declaration { value: None, .. } => Vec::new(),
+            Expr::Unary { operand, .. } => vec![std::mem::take(&mut **operand)],
+            Expr::Binary { left, right, .. } => {
+                vec![std::mem::take(&mut **left), std::mem::take(&mut **right)]
+            }
+            Expr::Call { args, .. } | Expr::Program(args) => std::mem::take(args),
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => vec![
+                std::mem::take(&mut **condition),
+                std::mem::take(&mut **then),
+                std::mem::take(&mut **otherwise),
+            ],
+            Expr::Assign { value, .. }
+            | Expr::Declaration {
+                value: Some(value), ..
+            } => vec![std::mem::take(&mut **value)],
+            // The body is shared with the function definition while it is defined
+            Expr::Function { body, .. } => {
+                Rc::get_mut(body).map(std::mem::take).into_iter().collect()
+            }
+        }
+    }
+
+    /// Notation of the node in the prefix and postfix forms
+    pub fn label(&self) -> String {
+        match self {
+            Expr::Num { token, .. } | Expr::Var(token) => token.lexeme.to_string(),
+            Expr::Unary { operator, .. } => unary_notation(operator).to_string(),
+            Expr::Binary { operator, .. } => type_to_string(&operator.token_type),
+            Expr::Call { name, args, .. } => call_notation(name, args.len()),
+            Expr::Conditional { keyword, .. } => match keyword.token_type {
+                TokenType::If => String::from("if"),
+                _ => String::from("?:"),
+            },
+            Expr::Assign { .. } => String::from("="),
+            Expr::Declaration {
+                declared_type,
+                name,
+                ..
+            } => declaration_notation(*declared_type, name),
+            Expr::Function { name, params, .. } => function_notation(name, params),
+            Expr::Program(_) => String::from("program"),
+            Expr::Error(_) => String::from("error"),
+        }
+    }
+
+    /// Whether a syntax error was recovered somewhere inside the expression,
+    /// type checks are skipped for it so that one error is reported once
+    pub fn has_error(&self) -> bool {
+        let mut pending = vec![self];
+        while let Some(expr) = pending.pop() {
+            if let Expr::Error(_) = expr {
+                return true;
+            }
+            pending.extend(expr.operands());
+        }
+        false
+    }
+}
+
+/// Dropped from a stack of the nodes left to drop, dropping each operand in
+/// its parent would take a stack frame per operator of a chain
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut pending = self.take_operands();
+        while let Some(mut expr) = pending.pop() {
+            pending.append(&mut expr.take_operands());
+        }
+    }
+}
+
+fn unary_notation(operator: &Token) -> &'static str {
+    match operator.token_type {
+        TokenType::Minus => "neg",
+        TokenType::Not => "!",
+        _ => "pos",
+    }
+}
+
+fn call_notation(name: &Token, arity: usize) -> String {
+    format!("{}/{}", name.lexeme, arity)
+}
+
+/// Label of a declaration, the parser writes it before the declared value
+pub fn declaration_notation(declared_type: Type, name: &Token) -> String {
+    format!("{} {}", declared_type, name.lexeme)
+}
+
+/// Label of a function definition, the parser writes it before the body
+pub fn function_notation(name: &Token, params: &[String]) -> String {
+    format!("{}({})", name.lexeme, params.join(", "))
+}
@@ -0,0 +1,143 @@
+use std::f32::consts::{E, PI, TAU};
+
+use super::types::{Type, Value};
+
+/// `function` gets as many numeric arguments as `arity` and returns a value
+/// of `result_type`, `None` when the result does not fit in an `int`
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&[Value]) -> Option<Value>,
+    pub result_type: fn(&[Type]) -> Type,
+}
+
+pub const BUILTINS: [Builtin; 13] = [
+    Builtin {
+        name: "sin",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().sin())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "cos",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().cos())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "tan",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().tan())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "sqrt",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().sqrt())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "log",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().log10())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "ln",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().ln())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "exp",
+        arity: 1,
+        function: |args| Some(Value::Float(args[0].as_float().exp())),
+        result_type: |_| Type::Float,
+    },
+    Builtin {
+        name: "abs",
+        arity: 1,
+        function: |args| match args[0] {
+            Value::Int(num) => num.checked_abs().map(Value::Int),
+            num => Some(Value::Float(num.as_float().abs())),
+        },
+        result_type: |types| types[0],
+    },
+    Builtin {
+        name: "min",
+        arity: 2,
+        function: |args| match (args[0], args[1]) {
+            (Value::Int(a), Value::Int(b)) => Some(Value::Int(a.min(b))),
+            (a, b) => Some(Value::Float(a.as_float().min(b.as_float()))),
+        },
+        result_type: |types| types[0].widen(types[1]),
+    },
+    Builtin {
+        name: "max",
+        arity: 2,
+        function: |args| match (args[0], args[1]) {
+            (Value::Int(a), Value::Int(b)) => Some(Value::Int(a.max(b))),
+            (a, b) => Some(Value::Float(a.as_float().max(b.as_float()))),
+        },
+        result_type: |types| types[0].widen(types[1]),
+    },
+    Builtin {
+        name: "floor",
+        arity: 1,
+        function: |args| rounded(args[0], f32::floor),
+        result_type: |_| Type::Int,
+    },
+    Builtin {
+        name: "ceil",
+        arity: 1,
+        function: |args| rounded(args[0], f32::ceil),
+        result_type: |_| Type::Int,
+    },
+    Builtin {
+        name: "round",
+        arity: 1,
+        function: |args| rounded(args[0], f32::round),
+        result_type: |_| Type::Int,
+    },
+];
+
+/// An `int` is already rounded, a `NaN` or a float out of the `int` range
+/// has no result
+fn rounded(num: Value, round: fn(f32) -> f32) -> Option<Value> {
+    let num = match num {
+        Value::Int(_) => return Some(num),
+        num => round(num.as_float()),
+    };
+    // `i64::MAX as f32` is 2^63, one past the largest `int`
+    (num >= i64::MIN as f32 && num < i64::MAX as f32).then_some(Value::Int(num as i64))
+}
+
+pub fn find_builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+/// Constants used where no variable of the same name is in scope, so `e` is
+/// still free to be a variable
+pub struct Constant {
+    pub name: &'static str,
+    pub value: f32,
+}
+
+pub const CONSTANTS: [Constant; 3] = [
+    Constant {
+        name: "π",
+        value: PI,
+    },
+    Constant {
+        name: "τ",
+        value: TAU,
+    },
+    Constant {
+        name: "e",
+        value: E,
+    },
+];
+
+pub fn find_constant(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|constant| constant.name == name)
+}
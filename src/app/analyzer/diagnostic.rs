@@ -0,0 +1,201 @@
+use std::fmt;
+
+use super::{
+    super::i18n::{text, tr},
+    error::{AnalyzerError, ErrorKind},
+    lexic::{Location, TokenType},
+    types::Type,
+};
+
+/// Role of a diagnostic line, so that each renderer may style it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Part {
+    Message,
+    Source,
+    Underline,
+    Hint,
+}
+
+/// An error shown rustc style, the input line with the span underlined:
+///
+/// ```text
+/// error[E0201] Símbolo inesperado '*', en 1:5, ...
+///  --> 1:5
+///   |
+/// 1 | 1 + * 2
+///   |     ^
+/// ```
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    message: String,
+    location: Location,
+    source: String,
+    underline: String,
+    hint: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(input: &str, error: &AnalyzerError) -> Self {
+        let (start, end) = error.span();
+        let start = start.min(input.len());
+        let end = end.clamp(start, input.len());
+        let line_start = input[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        let line_end = input[start..]
+            .find('\n')
+            .map_or(input.len(), |newline| start + newline);
+
+        // Tabs are kept in the padding so the caret lines up with the source
+        let mut underline: String = input[line_start..start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        underline.push('^');
+        let width = input[start..end.min(line_end)].chars().count();
+        underline.extend(std::iter::repeat_n('~', width.saturating_sub(1)));
+
+        Diagnostic {
+            message: if error.kind().is_fatal() {
+                format!("error{}", error)
+            } else {
+                format!("warning{}", error)
+            },
+            location: error.location(),
+            source: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            underline,
+            hint: hint(error, start >= input.len()),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<(Part, String)> {
+        let number = self.location.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let mut lines = vec![
+            (Part::Message, self.message.clone()),
+            (Part::Source, format!("{}--> {}", gutter, self.location)),
+            (Part::Source, format!("{} |", gutter)),
+            (Part::Source, format!("{} | {}", number, self.source)),
+            (Part::Underline, format!("{} | {}", gutter, self.underline)),
+        ];
+        if let Some(hint) = &self.hint {
+            lines.push((
+                Part::Hint,
+                format!("{} = {}: {}", gutter, text("diagnostic.help"), hint),
+            ));
+        }
+        lines
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.lines().into_iter().map(|(_, line)| line).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Plain text rendering of every error, for non interactive use
+pub fn render(input: &str, errors: &[AnalyzerError]) -> String {
+    let diagnostics: Vec<String> = errors
+        .iter()
+        .map(|error| Diagnostic::new(input, error).to_string())
+        .collect();
+    diagnostics.join("\n\n")
+}
+
+fn hint(error: &AnalyzerError, at_end: bool) -> Option<String> {
+    let lexeme: &dyn fmt::Display = &error.lexeme();
+    let hint = match error.kind() {
+        ErrorKind::UnterminatedParenthesis => tr("hint.unterminated_parenthesis", &[]),
+        ErrorKind::UnterminatedComment => tr("hint.unterminated_comment", &[]),
+        ErrorKind::UnexpectedToken if at_end => tr("hint.unexpected_end", &[]),
+        ErrorKind::UnexpectedToken if error.expected().contains(&TokenType::Semicolon) => {
+            tr("hint.separator", &[])
+        }
+        ErrorKind::MissingDigit => tr("hint.missing_digit", &[lexeme]),
+        ErrorKind::UndefinedFunction => tr("hint.undefined_function", &[]),
+        ErrorKind::UndefinedVariable => tr("hint.undefined_variable", &[lexeme]),
+        ErrorKind::TypeMismatch {
+            expected: Type::Int,
+            found: Type::Float,
+        } => tr("hint.int_from_float", &[]),
+        ErrorKind::Redeclaration { .. } => tr("hint.redeclaration", &[lexeme]),
+        ErrorKind::UseBeforeDeclaration { .. } => tr("hint.use_before_declaration", &[]),
+        ErrorKind::Uninitialized => tr("hint.uninitialized", &[lexeme]),
+        ErrorKind::BranchMismatch { .. } => tr("hint.branch_mismatch", &[]),
+        ErrorKind::RecursionLimit { nested: true, .. } => tr("hint.recursion_limit", &[]),
+        ErrorKind::RecursionLimit { .. } => tr("hint.call_limit", &[]),
+        _ => return None,
+    };
+    Some(hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::sintactic::Analyzer, *};
+
+    fn diagnostic(input: &str) -> Vec<(Part, String)> {
+        let errors = Analyzer::new(input, &Default::default()).analyze().errors;
+        Diagnostic::new(input, &errors[0]).lines()
+    }
+
+    fn underline(input: &str) -> String {
+        diagnostic(input)
+            .into_iter()
+            .find(|(part, _)| *part == Part::Underline)
+            .map(|(_, line)| line)
+            .unwrap()
+    }
+
+    #[test]
+    fn tabs_are_kept_before_the_caret() {
+        assert_eq!(underline("1 +\t* 2"), "  |    \t^");
+        assert_eq!(underline("\tx = 1 ÷ 0"), "  | \t      ^");
+    }
+
+    #[test]
+    fn carets_count_characters_not_bytes() {
+        assert_eq!(underline("año + * 2"), "  |       ^");
+        assert_eq!(
+            underline("int área = 1; int área = 2"),
+            "  |                   ^~~~"
+        );
+    }
+
+    #[test]
+    fn only_the_line_of_the_error_is_shown() {
+        let lines = diagnostic("1 +\n  2 * * 3\n4");
+        let parts: Vec<Part> = lines.iter().map(|(part, _)| *part).collect();
+        assert_eq!(
+            parts,
+            [
+                Part::Message,
+                Part::Source,
+                Part::Source,
+                Part::Source,
+                Part::Underline
+            ]
+        );
+        assert_eq!(lines[1].1, " --> 2:7");
+        assert_eq!(lines[3].1, "2 |   2 * * 3");
+        assert_eq!(lines[4].1, "  |       ^");
+    }
+
+    #[test]
+    fn parentheses_left_open_have_a_hint() {
+        let lines = diagnostic("(1 + 2");
+        assert_eq!(lines[4].1, "  | ^");
+        assert_eq!(
+            lines[5],
+            (
+                Part::Hint,
+                format!(
+                    "  = {}: {}",
+                    text("diagnostic.help"),
+                    tr("hint.unterminated_parenthesis", &[])
+                )
+            )
+        );
+    }
+}
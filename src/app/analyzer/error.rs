@@ -1,27 +1,331 @@
 use std::{error, fmt};
 
+use super::{
+    super::i18n::{text, tr},
+    lexic::{type_to_string, Location, Span, Token, TokenType},
+    types::Type,
+};
+
+/// What went wrong, with the data each message needs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ErrorKind {
+    /// A character that starts no token
+    #[default]
+    UnexpectedCharacter,
+    /// A token the grammar does not allow here, see `expected`
+    UnexpectedToken,
+    /// The input ends before the `)` matching a `(`
+    UnterminatedParenthesis,
+    /// A number without digits after `.`, `e` or its sign
+    MissingDigit,
+    /// A number followed by a second `.`
+    MalformedNumber,
+    /// The input ends inside a `/* ... */` comment
+    UnterminatedComment,
+    DivisionByZero,
+    UndefinedFunction,
+    WrongArity {
+        expected: usize,
+        found: usize,
+    },
+    DuplicateParameter,
+    /// More than `limit` calls nested in one another or, when not `nested`,
+    /// made by one statement
+    RecursionLimit {
+        limit: usize,
+        nested: bool,
+    },
+    TypeMismatch {
+        expected: Type,
+        found: Type,
+    },
+    /// An int out of the range of `i64` or a float literal out of `f32`
+    IntegerOverflow,
+    /// An identifier with no value, only a warning: the statement is still
+    /// analyzed but has no value
+    UndefinedVariable,
+    Redeclaration {
+        previous: Location,
+    },
+    UseBeforeDeclaration {
+        declaration: Location,
+    },
+    Uninitialized,
+    InvalidOperand {
+        found: Vec<Type>,
+    },
+    BranchMismatch {
+        then: Type,
+        otherwise: Type,
+    },
+}
+
+impl ErrorKind {
+    /// Stable identifier of the kind: lexical errors are `E01xx`, syntactic
+    /// `E02xx`, semantic `E03xx` and the ones found while evaluating `E04xx`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::UnexpectedCharacter => "E0101",
+            ErrorKind::MissingDigit => "E0102",
+            ErrorKind::MalformedNumber => "E0103",
+            ErrorKind::UnterminatedComment => "E0104",
+            ErrorKind::UnexpectedToken => "E0201",
+            ErrorKind::UnterminatedParenthesis => "E0202",
+            ErrorKind::UndefinedFunction => "E0301",
+            ErrorKind::WrongArity { .. } => "E0302",
+            ErrorKind::DuplicateParameter => "E0303",
+            ErrorKind::TypeMismatch { .. } => "E0304",
+            ErrorKind::Redeclaration { .. } => "E0305",
+            ErrorKind::UseBeforeDeclaration { .. } => "E0306",
+            ErrorKind::Uninitialized => "E0307",
+            ErrorKind::InvalidOperand { .. } => "E0308",
+            ErrorKind::BranchMismatch { .. } => "E0309",
+            ErrorKind::IntegerOverflow => "E0310",
+            ErrorKind::UndefinedVariable => "E0311",
+            ErrorKind::DivisionByZero => "E0401",
+            ErrorKind::RecursionLimit { .. } => "E0402",
+        }
+    }
+
+    /// Whether the kind keeps the statement from being evaluated
+    pub fn is_fatal(&self) -> bool {
+        *self != ErrorKind::UndefinedVariable
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct AnalyzerError {
-    character: String,
-    pos: u32,
-    expected: String,
+    kind: ErrorKind,
+    lexeme: String,
+    span: Span,
+    location: Location,
+    expected: Vec<TokenType>,
 }
+
 impl AnalyzerError {
-    pub fn new(character: &str, pos: u32, expected: &str) -> Self {
+    fn at(kind: ErrorKind, token: &Token) -> Self {
+        AnalyzerError {
+            kind,
+            lexeme: token.lexeme.to_string(),
+            span: token.span,
+            location: token.location,
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn unexpected_character(character: char, offset: usize, location: Location) -> Self {
+        AnalyzerError {
+            kind: ErrorKind::UnexpectedCharacter,
+            lexeme: String::from(character),
+            span: (offset, offset + character.len_utf8()),
+            location,
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn unexpected_token(token: &Token, expected: &[TokenType]) -> Self {
+        let mut error = AnalyzerError {
+            expected: expected.to_vec(),
+            ..Self::at(ErrorKind::UnexpectedToken, token)
+        };
+        if token.token_type == TokenType::EOF {
+            error.lexeme = type_to_string(&TokenType::EOF);
+        } else if token.lexeme == "\n" {
+            error.lexeme = text("token.newline").to_string();
+        }
+        error
+    }
+
+    /// Points at the `(` left open
+    pub fn unterminated_parenthesis(open: &Token) -> Self {
         AnalyzerError {
-            character: String::from(character),
-            expected: String::from(expected),
-            pos,
+            expected: vec![TokenType::ClosingParenthesis],
+            ..Self::at(ErrorKind::UnterminatedParenthesis, open)
         }
     }
+
+    pub fn missing_digit(lexeme: &str, start: usize, location: Location) -> Self {
+        Self::in_number(ErrorKind::MissingDigit, lexeme, start, location)
+    }
+
+    pub fn malformed_number(lexeme: &str, start: usize, location: Location) -> Self {
+        Self::in_number(ErrorKind::MalformedNumber, lexeme, start, location)
+    }
+
+    fn in_number(kind: ErrorKind, lexeme: &str, start: usize, location: Location) -> Self {
+        AnalyzerError {
+            kind,
+            lexeme: String::from(lexeme),
+            span: (start, start + lexeme.len()),
+            location,
+            expected: Vec::new(),
+        }
+    }
+
+    /// Points at the `/*` left open
+    pub fn unterminated_comment(start: usize, location: Location) -> Self {
+        AnalyzerError {
+            kind: ErrorKind::UnterminatedComment,
+            lexeme: String::from("/*"),
+            span: (start, start + 2),
+            location,
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn division_by_zero(operator: &Token) -> Self {
+        Self::at(ErrorKind::DivisionByZero, operator)
+    }
+
+    pub fn undefined_function(name: &Token) -> Self {
+        Self::at(ErrorKind::UndefinedFunction, name)
+    }
+
+    pub fn wrong_arity(name: &Token, expected: usize, found: usize) -> Self {
+        Self::at(ErrorKind::WrongArity { expected, found }, name)
+    }
+
+    pub fn duplicate_parameter(name: &Token) -> Self {
+        Self::at(ErrorKind::DuplicateParameter, name)
+    }
+
+    pub fn recursion_limit(name: &Token, limit: usize) -> Self {
+        Self::at(
+            ErrorKind::RecursionLimit {
+                limit,
+                nested: true,
+            },
+            name,
+        )
+    }
+
+    pub fn call_limit(name: &Token, limit: usize) -> Self {
+        Self::at(
+            ErrorKind::RecursionLimit {
+                limit,
+                nested: false,
+            },
+            name,
+        )
+    }
+
+    pub fn type_mismatch(name: &Token, expected: Type, found: Type) -> Self {
+        Self::at(ErrorKind::TypeMismatch { expected, found }, name)
+    }
+
+    pub fn redeclaration(name: &Token, previous: Location) -> Self {
+        Self::at(ErrorKind::Redeclaration { previous }, name)
+    }
+
+    /// Points at the usage, `declaration` is where the declaration is
+    pub fn use_before_declaration(usage: &Token, declaration: Location) -> Self {
+        Self::at(ErrorKind::UseBeforeDeclaration { declaration }, usage)
+    }
+
+    pub fn uninitialized(name: &Token) -> Self {
+        Self::at(ErrorKind::Uninitialized, name)
+    }
+
+    pub fn invalid_operand(operator: &Token, found: &[Type]) -> Self {
+        let mut found = found.to_vec();
+        found.dedup();
+        Self::at(ErrorKind::InvalidOperand { found }, operator)
+    }
+
+    pub fn branch_mismatch(keyword: &Token, then: Type, otherwise: Type) -> Self {
+        Self::at(ErrorKind::BranchMismatch { then, otherwise }, keyword)
+    }
+
+    pub fn integer_overflow(token: &Token) -> Self {
+        Self::at(ErrorKind::IntegerOverflow, token)
+    }
+
+    pub fn undefined_variable(name: &Token) -> Self {
+        Self::at(ErrorKind::UndefinedVariable, name)
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    /// Byte offsets of the offending input
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn position(&self) -> usize {
+        self.span.0
+    }
+
+    /// Line and column where the error starts, the one shown in messages
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    /// Tokens that would have been accepted, only for syntax errors
+    pub fn expected(&self) -> &[TokenType] {
+        &self.expected
+    }
+}
+
+/// `'a'`, `'a' o 'b'`, `'a', 'b' o 'c'`
+fn expected_list(expected: &[TokenType]) -> String {
+    let names: Vec<String> = expected
+        .iter()
+        .map(|token_type| format!("'{}'", type_to_string(token_type)))
+        .collect();
+    join(&names, "list.or")
 }
+
+/// Joins with commas except for the last item, which is joined by the
+/// conjunction of the `list` message
+fn join(items: &[String], list: &str) -> String {
+    match items.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => tr(list, &[&rest.join(", "), last]),
+        None => String::new(),
+    }
+}
+
 impl error::Error for AnalyzerError {}
 impl fmt::Display for AnalyzerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Caracter inesperado '{}', en la posición {}, se esperaba: '{}'",
-            self.character, self.pos, self.expected
-        )
+        let (lexeme, pos) = (&self.lexeme, self.location);
+        let key = format!("error.{}", self.code());
+        let message = match &self.kind {
+            ErrorKind::UnexpectedToken => tr(&key, &[lexeme, &pos, &expected_list(&self.expected)]),
+            ErrorKind::WrongArity { expected, found } => tr(&key, &[lexeme, &pos, expected, found]),
+            ErrorKind::RecursionLimit {
+                limit,
+                nested: true,
+            } => tr(&key, &[lexeme, &pos, limit]),
+            ErrorKind::RecursionLimit { limit, .. } => {
+                tr("error.E0402.calls", &[lexeme, &pos, limit])
+            }
+            ErrorKind::TypeMismatch { expected, found } => {
+                tr(&key, &[lexeme, &pos, expected, found])
+            }
+            ErrorKind::Redeclaration { previous } => tr(&key, &[lexeme, &pos, previous]),
+            ErrorKind::UseBeforeDeclaration { declaration } => {
+                tr(&key, &[lexeme, &pos, declaration])
+            }
+            ErrorKind::InvalidOperand { found } => {
+                let found: Vec<String> = found.iter().map(|found| found.to_string()).collect();
+                tr(&key, &[lexeme, &pos, &join(&found, "list.and")])
+            }
+            ErrorKind::BranchMismatch { then, otherwise } => {
+                tr(&key, &[lexeme, &pos, then, otherwise])
+            }
+            _ => tr(&key, &[lexeme, &pos]),
+        };
+        write!(f, "[{}] {}", self.code(), message)
     }
 }
@@ -1,35 +1,167 @@
-use super::error::AnalyzerError;
+use std::{borrow::Cow, fmt, str::CharIndices};
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use super::{super::i18n::text, error::AnalyzerError};
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub enum TokenType {
     Number,
     Id,
+    IntType,
+    FloatType,
     Plus,
     Minus,
     Asterisk,
     Slash,
+    Percent,
+    Div,
+    Caret,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+    Not,
+    If,
+    Then,
+    Else,
+    Question,
+    Colon,
+    Assign,
+    Semicolon,
+    Comma,
     OpenParenthesis,
     ClosingParenthesis,
     #[default]
     EOF,
 }
 
+/// Line and column of a character, both start at 1
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl TokenType {
+    /// Whether a line break after the token leaves the statement open, as
+    /// after an operator such as `+`, `=` or `?`, or when there is no
+    /// statement yet
+    fn continues_line(&self) -> bool {
+        matches!(
+            self,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Asterisk
+                | TokenType::Slash
+                | TokenType::Percent
+                | TokenType::Div
+                | TokenType::Caret
+                | TokenType::Less
+                | TokenType::LessEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Equal
+                | TokenType::NotEqual
+                | TokenType::And
+                | TokenType::Or
+                | TokenType::Question
+                | TokenType::Colon
+                | TokenType::Assign
+                | TokenType::Semicolon
+                | TokenType::OpenParenthesis
+        )
+    }
+}
+
+/// Byte offsets in the input of the first character and one past the last one
+pub type Span = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    /// `// ...` up to the end of the line
+    LineComment,
+    /// `/* ... */`, they may be nested
+    BlockComment,
+}
+
+/// Input the parser does not see, kept so that it can be written back
+#[derive(Debug, Clone)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: Cow<'a, str>,
+    pub span: Span,
+    pub location: Location,
+}
+
+/// The lexeme borrows the input while parsing, tokens kept in the AST or in
+/// errors are turned into owned ones with `owned`. The comments before a token
+/// are in its `trivia`, the ones at the end of the input in the `EOF` token
 #[derive(Debug, Clone, Default)]
-pub struct Token {
-    pub position: u32,
-    pub lexeme: String,
+pub struct Token<'a> {
+    pub span: Span,
+    pub location: Location,
+    pub lexeme: Cow<'a, str>,
     pub token_type: TokenType,
+    pub trivia: Vec<Trivia<'a>>,
 }
 
+impl Token<'_> {
+    pub fn owned(&self) -> Token<'static> {
+        Token {
+            span: self.span,
+            location: self.location,
+            lexeme: Cow::Owned(self.lexeme.to_string()),
+            token_type: self.token_type.clone(),
+            trivia: self
+                .trivia
+                .iter()
+                .map(|trivia| Trivia {
+                    text: Cow::Owned(trivia.text.to_string()),
+                    ..*trivia
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Walks the input once, `chars` is the cursor after `current`, which is at
+/// byte `offset`. The end of the input is `'\0'` at `offset == input.len()`.
+/// Lexical errors do not stop it: the offending characters are skipped and the
+/// errors kept in `errors`. A line break is a `Semicolon` token unless it is
+/// inside parentheses or the line ends in an operator
 #[derive(Debug, Clone)]
-pub struct LexicAnalyzer {
+pub struct LexicAnalyzer<'a> {
+    input: &'a str,
+    chars: CharIndices<'a>,
     pub current: char,
-    pub input: String,
-    pub pos: u32,
-    pub token: Option<Token>,
+    pub offset: usize,
+    pub location: Location,
+    pub previous_end: usize,
+    pub token: Token<'a>,
+    pub errors: Vec<AnalyzerError>,
+    /// Whether characters were skipped right before `token`
+    pub after_error: bool,
+    /// Comments scanned since the last token, they go with the next one
+    trivia: Vec<Trivia<'a>>,
+    /// Open parentheses before `current`
+    depth: usize,
+    /// Whether a line break at `current` leaves the statement open
+    continues: bool,
+    finished: bool,
 }
 
-pub type LexicResult = Result<Token, AnalyzerError>;
+pub type LexicResult<'a> = Result<Token<'a>, AnalyzerError>;
 
 pub fn type_to_string(t: &TokenType) -> String {
     match t {
@@ -37,171 +169,574 @@ pub fn type_to_string(t: &TokenType) -> String {
         TokenType::OpenParenthesis => String::from("("),
         TokenType::Asterisk => String::from("*"),
         TokenType::Slash => String::from("/"),
+        TokenType::Percent => String::from("%"),
+        TokenType::Div => String::from("div"),
+        TokenType::IntType => String::from("int"),
+        TokenType::FloatType => String::from("float"),
+        TokenType::Caret => String::from("^"),
+        TokenType::Less => String::from("<"),
+        TokenType::LessEqual => String::from("<="),
+        TokenType::Greater => String::from(">"),
+        TokenType::GreaterEqual => String::from(">="),
+        TokenType::Equal => String::from("=="),
+        TokenType::NotEqual => String::from("!="),
+        TokenType::And => String::from("&&"),
+        TokenType::Or => String::from("||"),
+        TokenType::Not => String::from("!"),
+        TokenType::If => String::from("if"),
+        TokenType::Then => String::from("then"),
+        TokenType::Else => String::from("else"),
+        TokenType::Question => String::from("?"),
+        TokenType::Colon => String::from(":"),
+        TokenType::Assign => String::from("="),
+        TokenType::Semicolon => String::from(";"),
+        TokenType::Comma => String::from(","),
         TokenType::Plus => String::from("+"),
         TokenType::Minus => String::from("-"),
-        TokenType::Number => String::from("número"),
-        TokenType::Id => String::from("identificador"),
-        TokenType::EOF => String::from("Final de archivo"),
+        TokenType::Number => text("token.number").to_string(),
+        TokenType::Id => text("token.id").to_string(),
+        TokenType::EOF => text("token.eof").to_string(),
     }
 }
 
-impl LexicAnalyzer {
-    pub fn new(input: &String) -> Self {
-        let mut input_copy = input.clone();
-        let current = input_copy.remove(0);
-        LexicAnalyzer {
-            pos: 1,
-            current,
-            input: input_copy,
-            token: None,
+/// Every token of the input and every lexical error, in order
+pub fn tokenize(input: &str) -> Vec<Result<Token<'_>, AnalyzerError>> {
+    LexicAnalyzer::new(input).collect()
+}
+
+fn keyword(lexeme: &str) -> Option<TokenType> {
+    match lexeme {
+        "div" => Some(TokenType::Div),
+        "int" => Some(TokenType::IntType),
+        "float" => Some(TokenType::FloatType),
+        "if" => Some(TokenType::If),
+        "then" => Some(TokenType::Then),
+        "else" => Some(TokenType::Else),
+        _ => None,
+    }
+}
+
+impl<'a> LexicAnalyzer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        let mut lexic = LexicAnalyzer {
+            input,
+            chars: input.char_indices(),
+            current: '\0',
+            offset: 0,
+            location: Location { line: 1, column: 1 },
+            previous_end: 0,
+            token: Token::default(),
+            errors: Vec::new(),
+            after_error: false,
+            trivia: Vec::new(),
+            depth: 0,
+            continues: true,
+            finished: false,
+        };
+        lexic.advance();
+        lexic
+    }
+
+    pub fn check_and_next(&mut self, expected: &TokenType) -> LexicResult<'a> {
+        let token = self.token.clone();
+        if *expected != token.token_type {
+            return Err(AnalyzerError::unexpected_token(
+                &token,
+                std::slice::from_ref(expected),
+            ));
         }
+        self.consume_token();
+        Ok(token)
     }
 
-    pub fn check_and_next(&mut self, expected: &TokenType) -> LexicResult {
-        if let Some(token) = self.token.clone() {
-            if *expected != token.token_type {
-                return Err(AnalyzerError::new(
-                    &(token.lexeme),
-                    token.position,
-                    &type_to_string(expected),
-                ));
+    /// Moves to the next valid token, the lexical errors found on the way are
+    /// added to `errors`
+    pub fn consume_token(&mut self) -> Token<'a> {
+        self.previous_end = self.token.span.1;
+        self.after_error = false;
+        self.token = loop {
+            match self.scan() {
+                Ok(token) => break token,
+                Err(error) => {
+                    self.errors.push(error);
+                    self.after_error = true;
+                }
             }
-            self.consume_token();
-            return Ok(token.clone());
+        };
+        self.token.clone()
+    }
+
+    pub fn peek_token(&self) -> Token<'a> {
+        self.cursor().consume_token()
+    }
+
+    /// Copy at the same token to look ahead, without the errors and comments
+    /// found so far
+    pub fn cursor(&self) -> Self {
+        LexicAnalyzer {
+            chars: self.chars.clone(),
+            token: self.token.clone(),
+            errors: Vec::new(),
+            trivia: Vec::new(),
+            ..*self
         }
-        Err(AnalyzerError::new(
-            &String::from(self.current),
-            self.pos,
-            &type_to_string(expected),
-        ))
     }
 
-    pub fn consume_token(&mut self) -> Option<Token> {
-        let mut token = Token {
-            position: self.pos,
-            lexeme: String::from(self.current),
-            token_type: TokenType::EOF,
+    /// Scans the token that starts at `current`. On error the offending
+    /// characters are consumed anyway, so scanning again goes on after them
+    fn scan(&mut self) -> LexicResult<'a> {
+        self.skip_trivia()?;
+        let (start, location) = (self.offset, self.location);
+        let token_type = if let Some(token_type) = self.multi_char_token() {
+            token_type
+        } else if let Some(token_type) = self.single_char_token() {
+            token_type
+        } else {
+            match self.current {
+                '0'..='9' | '.' => {
+                    self.number()?;
+                    TokenType::Number
+                }
+                c if c == '_' || is_xid_start(c) => {
+                    self.id();
+                    keyword(&self.input[start..self.offset]).unwrap_or(TokenType::Id)
+                }
+                '\0' if self.at_end() => TokenType::EOF,
+                _ => {
+                    let error = self.unexpected_character();
+                    self.next_char();
+                    return Err(error);
+                }
+            }
         };
-        while self.current == ' ' {
-            self.next_char();
-        }
-        if let Some(token) = self.single_char_token() {
-            self.token = Some(token.clone());
-            return Some(token);
+        match token_type {
+            TokenType::OpenParenthesis => self.depth += 1,
+            TokenType::ClosingParenthesis => self.depth = self.depth.saturating_sub(1),
+            _ => {}
         }
-        match self.current {
-            '0'..='9' => {
-                token.token_type = TokenType::Number;
-                if let Some(num) = self.number() {
-                    token.lexeme = num;
+        self.continues = token_type.continues_line();
+        Ok(Token {
+            span: (start, self.offset),
+            location,
+            lexeme: Cow::Borrowed(&self.input[start..self.offset]),
+            token_type,
+            trivia: std::mem::take(&mut self.trivia),
+        })
+    }
+
+    /// Skips whitespace and comments, the comments are kept in `trivia`. It
+    /// stops at a line break that ends the statement
+    fn skip_trivia(&mut self) -> Result<(), AnalyzerError> {
+        loop {
+            while matches!(self.current, ' ' | '\t' | '\n' | '\r') {
+                if self.current == '\n' && self.depth == 0 && !self.continues {
+                    return Ok(());
                 }
+                self.next_char();
             }
-            'a'..='z' | 'A'..='Z' | '_' => {
-                token.token_type = TokenType::Id;
-                if let Some(id) = self.id() {
-                    token.lexeme = id;
+            let (start, location) = (self.offset, self.location);
+            let (kind, closed) = match (self.current, self.peek_char()) {
+                ('/', Some('/')) => {
+                    while self.current != '\n' && !self.at_end() {
+                        self.next_char();
+                    }
+                    (TriviaKind::LineComment, true)
                 }
+                ('/', Some('*')) => (TriviaKind::BlockComment, self.block_comment()),
+                _ => return Ok(()),
+            };
+            self.trivia.push(Trivia {
+                kind,
+                text: Cow::Borrowed(&self.input[start..self.offset]),
+                span: (start, self.offset),
+                location,
+            });
+            if !closed {
+                return Err(AnalyzerError::unterminated_comment(start, location));
             }
-            '\0' => (),
-            _ => {
-                return None;
+        }
+    }
+
+    /// `/* ... */` with any number of nested comments, an unterminated one
+    /// takes the rest of the input. Returns whether it was closed
+    fn block_comment(&mut self) -> bool {
+        let mut depth = 0;
+        loop {
+            match (self.current, self.peek_char()) {
+                ('/', Some('*')) => depth += 1,
+                ('*', Some('/')) => depth -= 1,
+                _ if self.at_end() => return false,
+                _ => {
+                    self.next_char();
+                    continue;
+                }
             }
-        };
-        self.token = Some(token.clone());
-        Some(token)
+            self.next_char();
+            self.next_char();
+            if depth == 0 {
+                return true;
+            }
+        }
     }
 
-    pub fn single_char_token(&mut self) -> Option<Token> {
-        let mut token = Token {
-            position: self.pos,
-            lexeme: String::from(self.current),
-            token_type: TokenType::EOF,
+    /// Comparison and logical operators, `<`, `>` and `!` are included since
+    /// they may be followed by `=`
+    fn multi_char_token(&mut self) -> Option<TokenType> {
+        let (token_type, len) = match (self.current, self.peek_char()) {
+            ('<', Some('=')) => (TokenType::LessEqual, 2),
+            ('>', Some('=')) => (TokenType::GreaterEqual, 2),
+            ('=', Some('=')) => (TokenType::Equal, 2),
+            ('!', Some('=')) => (TokenType::NotEqual, 2),
+            ('&', Some('&')) => (TokenType::And, 2),
+            ('|', Some('|')) => (TokenType::Or, 2),
+            ('<', _) => (TokenType::Less, 1),
+            ('>', _) => (TokenType::Greater, 1),
+            ('!', _) => (TokenType::Not, 1),
+            _ => return None,
         };
-        match self.current {
-            '(' => token.token_type = TokenType::OpenParenthesis,
-            ')' => token.token_type = TokenType::ClosingParenthesis,
-            '-' => token.token_type = TokenType::Minus,
-            '+' => token.token_type = TokenType::Plus,
-            '/' => token.token_type = TokenType::Slash,
-            '*' => token.token_type = TokenType::Asterisk,
-            _ => {
-                return None;
+        for _ in 0..len {
+            self.next_char();
+        }
+        Some(token_type)
+    }
+
+    /// `×`, `÷` and `−` are accepted as `*`, `/` and `-`
+    fn single_char_token(&mut self) -> Option<TokenType> {
+        let token_type = match self.current {
+            '(' => TokenType::OpenParenthesis,
+            ')' => TokenType::ClosingParenthesis,
+            '-' | '−' => TokenType::Minus,
+            '+' => TokenType::Plus,
+            '/' | '÷' => TokenType::Slash,
+            '*' if self.peek_char() == Some('*') => {
+                self.next_char();
+                TokenType::Caret
             }
+            '*' | '×' => TokenType::Asterisk,
+            '%' => TokenType::Percent,
+            '^' => TokenType::Caret,
+            '=' => TokenType::Assign,
+            ';' | '\n' => TokenType::Semicolon,
+            ',' => TokenType::Comma,
+            '?' => TokenType::Question,
+            ':' => TokenType::Colon,
+            _ => return None,
         };
         self.next_char();
-        return Some(token);
+        Some(token_type)
+    }
+
+    fn unexpected_character(&self) -> AnalyzerError {
+        AnalyzerError::unexpected_character(self.current, self.offset, self.location)
+    }
+
+    fn at_end(&self) -> bool {
+        self.offset == self.input.len()
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.chars.clone().next().map(|(_, c)| c)
     }
 
+    /// Moves to the next character, `offset` and `location` always point at
+    /// `current` so the end of the input is one past the last character
     fn next_char(&mut self) {
-        if self.input.len() > 0 {
-            self.pos += 1;
-            self.current = self.input.remove(0);
-            return ();
+        if self.at_end() {
+            return;
         }
-        self.current = '\0';
+        if self.current == '\n' {
+            self.location.line += 1;
+            self.location.column = 1;
+        } else {
+            self.location.column += 1;
+        }
+        self.advance();
     }
 
-    pub fn number(&mut self) -> Option<String> {
-        if let Some(digit) = self.digit() {
-            return Some(self.rest_num(&digit.to_string()));
-        }
-        None
+    fn advance(&mut self) {
+        (self.offset, self.current) = self.chars.next().unwrap_or((self.input.len(), '\0'));
     }
 
-    pub fn rest_num(&mut self, prev: &str) -> String {
-        if let Some(digit) = self.digit() {
-            let mut next = String::from(prev);
-            next.push(digit);
-            return self.rest_num(&next);
+    /// `digits (. digits)? ((e|E) (+|-)? digits)?`, the integer part may be
+    /// missing as in `.5`
+    fn number(&mut self) -> Result<(), AnalyzerError> {
+        let (start, location) = (self.offset, self.location);
+        self.digits();
+        if self.current == '.' {
+            self.next_char();
+            self.required_digits(start, location)?;
+        }
+        if self.current == 'e' || self.current == 'E' {
+            self.next_char();
+            if self.current == '+' || self.current == '-' {
+                self.next_char();
+            }
+            self.required_digits(start, location)?;
+        }
+        if self.current == '.' {
+            // The rest of the number goes with the error, `1.2.3` is a single one
+            while self.current == '.' || self.current.is_ascii_digit() {
+                self.next_char();
+            }
+            let lexeme = &self.input[start..self.offset];
+            return Err(AnalyzerError::malformed_number(lexeme, start, location));
         }
-        return String::from(prev);
+        Ok(())
     }
 
-    pub fn id(&mut self) -> Option<String> {
-        if let Some(letter) = self.letter() {
-            return Some(self.rest_id(&letter.to_string()));
-        }
-        if self.current == '_' {
-            return Some(self.rest_id(&self.current.to_string()));
+    fn required_digits(&mut self, start: usize, location: Location) -> Result<(), AnalyzerError> {
+        if self.digits() == 0 {
+            let lexeme = &self.input[start..self.offset];
+            return Err(AnalyzerError::missing_digit(lexeme, start, location));
         }
-        return None;
+        Ok(())
     }
 
-    pub fn rest_id(&mut self, prev: &str) -> String {
-        let mut next = String::from(prev);
-        if let Some(letter) = self.letter() {
-            next.push(letter);
-            return self.rest_id(&next);
+    /// Skips a run of digits, returns how many there were
+    fn digits(&mut self) -> usize {
+        let mut count = 0;
+        while self.current.is_ascii_digit() {
+            self.next_char();
+            count += 1;
         }
-        if self.current == '_' {
-            next.push(self.current);
+        count
+    }
+
+    /// Unicode identifiers, `(XID_Start | _) XID_Continue*`, like `año` or `α`
+    fn id(&mut self) {
+        while is_xid_continue(self.current) {
             self.next_char();
-            return self.rest_id(&next);
         }
-        if let Some(digit) = self.digit() {
-            next.push(digit);
-            return self.rest_id(&next);
+    }
+}
+
+/// The token stream on its own, ending with `EOF`. Lexical errors take the
+/// place of the characters they skip
+impl<'a> Iterator for LexicAnalyzer<'a> {
+    type Item = LexicResult<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
-        String::from(prev)
+        let item = self.scan();
+        self.finished = matches!(&item, Ok(token) if token.token_type == TokenType::EOF);
+        Some(item)
     }
+}
 
-    pub fn letter(&mut self) -> Option<char> {
-        let str = match self.current {
-            'a'..='z' | 'A'..='Z' => self.current,
-            _ => {
-                return None;
-            }
-        };
-        self.next_char();
-        Some(str)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Type of each token, or the code of each error, with its lexeme
+    fn scan(input: &str) -> Vec<(String, String)> {
+        tokenize(input)
+            .into_iter()
+            .map(|item| match item {
+                Ok(token) => (format!("{:?}", token.token_type), token.lexeme.to_string()),
+                Err(error) => (error.code().to_string(), error.lexeme().to_string()),
+            })
+            .collect()
     }
 
-    pub fn digit(&mut self) -> Option<char> {
-        if !self.current.is_digit(10) {
-            return None;
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(kind, lexeme)| (kind.to_string(), lexeme.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn number_literals() {
+        for input in [".5", "2.5E+10", "1.5e-3", "007"] {
+            assert_eq!(scan(input), pairs(&[("Number", input), ("EOF", "")]));
         }
-        let res = self.current;
-        self.next_char();
-        Some(res)
+    }
+
+    #[test]
+    fn malformed_numbers_are_one_error() {
+        assert_eq!(scan("1."), pairs(&[("E0102", "1."), ("EOF", "")]));
+        assert_eq!(scan("1e"), pairs(&[("E0102", "1e"), ("EOF", "")]));
+        assert_eq!(
+            scan("1e+ 2"),
+            pairs(&[("E0102", "1e+"), ("Number", "2"), ("EOF", "")])
+        );
+        assert_eq!(scan("1.2.3"), pairs(&[("E0103", "1.2.3"), ("EOF", "")]));
+    }
+
+    #[test]
+    fn line_breaks_are_separators_outside_expressions() {
+        assert_eq!(
+            scan("1\n-2"),
+            pairs(&[
+                ("Number", "1"),
+                ("Semicolon", "\n"),
+                ("Minus", "-"),
+                ("Number", "2"),
+                ("EOF", "")
+            ])
+        );
+        assert_eq!(
+            scan("\n(1\n) *\n2\n"),
+            pairs(&[
+                ("OpenParenthesis", "("),
+                ("Number", "1"),
+                ("ClosingParenthesis", ")"),
+                ("Asterisk", "*"),
+                ("Number", "2"),
+                ("Semicolon", "\n"),
+                ("EOF", "")
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_input_is_only_the_end() {
+        assert_eq!(scan(""), pairs(&[("EOF", "")]));
+        let tokens = tokenize("  \n\t");
+        assert_eq!(tokens.len(), 1);
+        let eof = tokens[0].as_ref().unwrap();
+        assert_eq!(eof.token_type, TokenType::EOF);
+        assert_eq!(eof.span, (4, 4));
+        assert_eq!(eof.location, Location { line: 2, column: 2 });
+    }
+
+    #[test]
+    fn the_token_stream_ends_after_eof() {
+        let mut lexic = LexicAnalyzer::new("1 $ x");
+        let number = lexic.next().unwrap().unwrap();
+        assert_eq!(
+            (number.token_type, number.span),
+            (TokenType::Number, (0, 1))
+        );
+        let error = lexic.next().unwrap().unwrap_err();
+        assert_eq!((error.code(), error.span()), ("E0101", (2, 3)));
+        let id = lexic.next().unwrap().unwrap();
+        assert_eq!((id.token_type, id.span), (TokenType::Id, (4, 5)));
+        assert_eq!(id.location, Location { line: 1, column: 5 });
+        let eof = lexic.next().unwrap().unwrap();
+        assert_eq!(eof.token_type, TokenType::EOF);
+        assert!(lexic.next().is_none());
+        assert!(lexic.next().is_none());
+    }
+
+    #[test]
+    fn tokenize_gives_the_lexemes_of_the_input() {
+        assert_eq!(
+            scan("f(x, 2) ** 3 >= 1 && !y"),
+            pairs(&[
+                ("Id", "f"),
+                ("OpenParenthesis", "("),
+                ("Id", "x"),
+                ("Comma", ","),
+                ("Number", "2"),
+                ("ClosingParenthesis", ")"),
+                ("Caret", "**"),
+                ("Number", "3"),
+                ("GreaterEqual", ">="),
+                ("Number", "1"),
+                ("And", "&&"),
+                ("Not", "!"),
+                ("Id", "y"),
+                ("EOF", "")
+            ])
+        );
+    }
+
+    /// Kind, text and span of the comments before each token
+    fn trivia(input: &str) -> Vec<Vec<(TriviaKind, String, Span)>> {
+        tokenize(input)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|token| {
+                token
+                    .trivia
+                    .iter()
+                    .map(|trivia| (trivia.kind, trivia.text.to_string(), trivia.span))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn comments_go_with_the_following_token() {
+        assert_eq!(
+            trivia("// a\n/* b */ x // c"),
+            [
+                vec![
+                    (TriviaKind::LineComment, String::from("// a"), (0, 4)),
+                    (TriviaKind::BlockComment, String::from("/* b */"), (5, 12))
+                ],
+                vec![(TriviaKind::LineComment, String::from("// c"), (15, 19))]
+            ]
+        );
+        assert_eq!(scan("1 // 2\n+ 3"), scan("1\n+ 3"));
+    }
+
+    #[test]
+    fn block_comments_nest() {
+        assert_eq!(
+            scan("/* a /* b */ c */ 1"),
+            pairs(&[("Number", "1"), ("EOF", "")])
+        );
+        assert_eq!(
+            trivia("/* a /* b */ c */ 1")[0],
+            [(
+                TriviaKind::BlockComment,
+                String::from("/* a /* b */ c */"),
+                (0, 17)
+            )]
+        );
+    }
+
+    #[test]
+    fn unterminated_comments_take_the_rest_of_the_input() {
+        assert_eq!(
+            scan("1 /* a /* b */"),
+            pairs(&[("Number", "1"), ("E0104", "/*"), ("EOF", "")])
+        );
+        let error = tokenize("1 /* a").remove(1).unwrap_err();
+        assert_eq!(error.span(), (2, 4));
+        assert_eq!(
+            trivia("1 /* a")[1],
+            [(TriviaKind::BlockComment, String::from("/* a"), (2, 6))]
+        );
+    }
+
+    #[test]
+    fn unicode_identifiers_and_operators() {
+        assert_eq!(
+            scan("año × π ÷ α1 − _x"),
+            pairs(&[
+                ("Id", "año"),
+                ("Asterisk", "×"),
+                ("Id", "π"),
+                ("Slash", "÷"),
+                ("Id", "α1"),
+                ("Minus", "−"),
+                ("Id", "_x"),
+                ("EOF", "")
+            ])
+        );
+        assert_eq!(scan("²"), pairs(&[("E0101", "²"), ("EOF", "")]));
+    }
+
+    #[test]
+    fn columns_count_characters_and_spans_bytes() {
+        let tokens: Vec<(Span, Location)> = tokenize("año − 1")
+            .into_iter()
+            .map(|token| token.map(|token| (token.span, token.location)).unwrap())
+            .collect();
+        let at = |column| Location { line: 1, column };
+        assert_eq!(
+            tokens,
+            [
+                ((0, 4), at(1)),
+                ((5, 8), at(5)),
+                ((9, 10), at(7)),
+                ((10, 10), at(8))
+            ]
+        );
     }
 }
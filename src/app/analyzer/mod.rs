@@ -1,3 +1,8 @@
-pub mod lexic;
+pub mod ast;
+pub mod builtins;
+pub mod diagnostic;
 pub mod error;
+pub mod lexic;
+pub mod semantic;
 pub mod sintactic;
+pub mod types;
@@ -0,0 +1,218 @@
+use super::{lexic::Location, types::Type};
+
+const GLOBAL_SCOPE: usize = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    Function,
+    Builtin,
+}
+
+impl SymbolKind {
+    pub fn is_function(&self) -> bool {
+        matches!(self, SymbolKind::Function | SymbolKind::Builtin)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub symbol_type: Type,
+    pub scope: usize,
+    pub declared_at: Option<Location>,
+    pub used_at: Vec<Location>,
+    pub initialized: bool,
+    /// Whether the type was declared, as in `int x`, and values of another
+    /// type are rejected
+    pub typed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub name: String,
+    pub parent: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    current: usize,
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        SymbolTable {
+            scopes: vec![Scope {
+                name: String::from("global"),
+                parent: None,
+            }],
+            current: GLOBAL_SCOPE,
+            symbols: Vec::new(),
+        }
+    }
+
+    pub fn enter_scope(&mut self, name: &str) {
+        self.scopes.push(Scope {
+            name: String::from(name),
+            parent: Some(self.current),
+        });
+        self.current = self.scopes.len() - 1;
+    }
+
+    pub fn exit_scope(&mut self) {
+        if let Some(parent) = self.scopes[self.current].parent {
+            self.current = parent;
+        }
+    }
+
+    pub fn declare(
+        &mut self,
+        name: &str,
+        kind: SymbolKind,
+        symbol_type: Type,
+        declared_at: Option<Location>,
+    ) -> &mut Symbol {
+        self.declare_in(self.current, name, kind, symbol_type, declared_at)
+    }
+
+    pub fn declare_global(
+        &mut self,
+        name: &str,
+        kind: SymbolKind,
+        symbol_type: Type,
+        declared_at: Option<Location>,
+    ) -> &mut Symbol {
+        self.declare_in(GLOBAL_SCOPE, name, kind, symbol_type, declared_at)
+    }
+
+    fn declare_in(
+        &mut self,
+        scope: usize,
+        name: &str,
+        kind: SymbolKind,
+        symbol_type: Type,
+        declared_at: Option<Location>,
+    ) -> &mut Symbol {
+        let found = self.symbols.iter().position(|symbol| {
+            symbol.scope == scope
+                && symbol.name == name
+                && symbol.kind.is_function() == kind.is_function()
+        });
+        let index = match found {
+            Some(index) => index,
+            None => {
+                self.symbols.push(Symbol {
+                    name: String::from(name),
+                    kind,
+                    symbol_type,
+                    scope,
+                    declared_at,
+                    used_at: Vec::new(),
+                    initialized: true,
+                    typed: false,
+                });
+                self.symbols.len() - 1
+            }
+        };
+        &mut self.symbols[index]
+    }
+
+    pub fn lookup_local(&mut self, name: &str) -> Option<&mut Symbol> {
+        let current = self.current;
+        self.symbols.iter_mut().find(|symbol| {
+            symbol.scope == current && symbol.name == name && !symbol.kind.is_function()
+        })
+    }
+
+    pub fn lookup(&mut self, name: &str) -> Option<&mut Symbol> {
+        self.find(name, false)
+    }
+
+    pub fn lookup_function(&mut self, name: &str) -> Option<&mut Symbol> {
+        self.find(name, true)
+    }
+
+    pub fn scope_name(&self, scope: usize) -> &str {
+        &self.scopes[scope].name
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    fn find(&mut self, name: &str, function: bool) -> Option<&mut Symbol> {
+        let mut scope = Some(self.current);
+        while let Some(current) = scope {
+            let found = self.symbols.iter().position(|symbol| {
+                symbol.scope == current
+                    && symbol.name == name
+                    && symbol.kind.is_function() == function
+            });
+            if let Some(index) = found {
+                return Some(&mut self.symbols[index]);
+            }
+            scope = self.scopes[current].parent;
+        }
+        None
+    }
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        SymbolTable::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_go_from_the_current_scope_to_the_global_one() {
+        let mut table = SymbolTable::new();
+        table.declare("x", SymbolKind::Variable, Type::Int, None);
+        table.enter_scope("f");
+        table.declare("x", SymbolKind::Parameter, Type::Float, None);
+        table.declare("y", SymbolKind::Parameter, Type::Int, None);
+        assert_eq!(table.lookup("x").unwrap().kind, SymbolKind::Parameter);
+        assert_eq!(table.lookup_local("y").unwrap().scope, 1);
+        table.exit_scope();
+        assert_eq!(table.lookup("x").unwrap().kind, SymbolKind::Variable);
+        assert!(table.lookup("y").is_none());
+        assert!(table.lookup_local("y").is_none());
+        assert_eq!(table.scope_name(1), "f");
+        table.exit_scope();
+        assert!(table.lookup("x").is_some());
+    }
+
+    #[test]
+    fn functions_and_variables_do_not_clash() {
+        let mut table = SymbolTable::new();
+        table.declare("f", SymbolKind::Variable, Type::Int, None);
+        table.enter_scope("g");
+        table.declare_global("f", SymbolKind::Function, Type::Float, None);
+        assert_eq!(table.lookup("f").unwrap().symbol_type, Type::Int);
+        assert_eq!(table.lookup_function("f").unwrap().scope, GLOBAL_SCOPE);
+        assert_eq!(table.symbols().len(), 2);
+    }
+
+    #[test]
+    fn declaring_again_returns_the_same_symbol() {
+        let mut table = SymbolTable::new();
+        let location = Location { line: 1, column: 5 };
+        table.declare("x", SymbolKind::Variable, Type::Int, Some(location));
+        table
+            .declare("x", SymbolKind::Variable, Type::Float, None)
+            .used_at
+            .push(location);
+        let symbols = table.symbols();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].symbol_type, Type::Int);
+        assert_eq!(symbols[0].declared_at, Some(location));
+        assert_eq!(symbols[0].used_at, [location]);
+    }
+}
@@ -1,200 +1,1778 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
+
 use super::{
-    error::AnalyzerError,
-    lexic::{type_to_string, LexicAnalyzer, TokenType},
+    ast::{declaration_notation, function_notation, Expr},
+    builtins::{find_builtin, find_constant},
+    error::{AnalyzerError, ErrorKind},
+    lexic::{type_to_string, LexicAnalyzer, Span, Token, TokenType},
+    semantic::{SymbolKind, SymbolTable},
+    types::{binary, binary_type, is_comparison, negate, Type, Value},
 };
-use crate::app::tree::TreeItem;
+use crate::app::tree::{Attributes, TreeItem};
+
+pub type Environment = BTreeMap<String, Value>;
+
+const MAX_CALL_DEPTH: usize = 64;
+/// Calls a statement may make, a function that calls itself twice per call
+/// would otherwise take exponential time within the depth limit
+const MAX_CALLS: usize = 100_000;
+
+/// Tokens an expression may continue from after a syntax error
+const SYNC: [TokenType; 22] = [
+    TokenType::ClosingParenthesis,
+    TokenType::Comma,
+    TokenType::Semicolon,
+    TokenType::Then,
+    TokenType::Else,
+    TokenType::Question,
+    TokenType::Colon,
+    TokenType::Plus,
+    TokenType::Minus,
+    TokenType::Asterisk,
+    TokenType::Slash,
+    TokenType::Div,
+    TokenType::Percent,
+    TokenType::Caret,
+    TokenType::Less,
+    TokenType::LessEqual,
+    TokenType::Greater,
+    TokenType::GreaterEqual,
+    TokenType::Equal,
+    TokenType::NotEqual,
+    TokenType::And,
+    TokenType::Or,
+];
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub return_type: Type,
+    body: Rc<Expr>,
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct Analyzed {
+    /// Postfix notation of the program, set once the whole input is analyzed
     pub postfix: String,
+    /// Byte range of the production in the postfix notation, the one of a
+    /// node spans the ones of its operands
+    pub postfix_range: (usize, usize),
     pub prefix: String,
     pub tree: TreeItem,
-    pub result: Option<f32>,
+    pub ast: Expr,
+    pub result: Option<Value>,
+    pub value_type: Type,
+    /// Names of the identifiers with no value, one per name
+    pub undefined: Vec<String>,
+    pub symbols: SymbolTable,
+    pub errors: Vec<AnalyzerError>,
+    /// Errors that do not stop the analysis, the first use of each
+    /// identifier with no value
+    pub warnings: Vec<AnalyzerError>,
 }
 
 pub type AnalyzerResult = Result<Analyzed, AnalyzerError>;
 
 #[derive(Debug, Clone)]
-pub struct Analyzer {
-    lexic: LexicAnalyzer,
+pub struct Analyzer<'a> {
+    lexic: LexicAnalyzer<'a>,
+    env: Environment,
+    scopes: Vec<Environment>,
+    functions: BTreeMap<String, Function>,
+    depth: usize,
+    /// Calls made by the statement being evaluated
+    calls: usize,
+    values: BTreeMap<Span, Value>,
+    /// Span of the AST node of each parse tree node that spans more input,
+    /// as a parenthesized factor, to look its value up
+    aliases: BTreeMap<Span, Span>,
+    /// Postfix notation written so far, each production appends its own
+    /// operator once its operands are written
+    postfix: String,
+    warnings: Vec<AnalyzerError>,
+    undeclared: Vec<Token<'a>>,
+    symbols: SymbolTable,
+    errors: Vec<AnalyzerError>,
+    /// Position and code of each reported error
+    reported: BTreeSet<(usize, &'static str)>,
 }
 
-impl Analyzer {
-    pub fn new(input: &String) -> Self {
+impl<'a> Analyzer<'a> {
+    pub fn new(input: &'a str, env: &Environment) -> Self {
+        let mut symbols = SymbolTable::new();
+        for (name, value) in env.iter() {
+            symbols.declare(name, SymbolKind::Variable, value.value_type(), None);
+        }
         Analyzer {
             lexic: LexicAnalyzer::new(input),
+            env: env.clone(),
+            scopes: Vec::new(),
+            functions: BTreeMap::new(),
+            depth: 0,
+            calls: 0,
+            values: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            postfix: String::new(),
+            warnings: Vec::new(),
+            undeclared: Vec::new(),
+            symbols,
+            errors: Vec::new(),
+            reported: BTreeSet::new(),
         }
     }
 
-    pub fn analyze(&mut self) -> AnalyzerResult {
+    /// Analyzes the whole input, lexical and syntax errors do not stop the
+    /// analysis: every error is collected in `errors` and `tree` holds what
+    /// was recovered
+    pub fn analyze(&mut self) -> Analyzed {
         self.lexic.consume_token();
-        let res = self.expr()?;
-        self.lexic.check_and_next(&TokenType::EOF)?;
-        Ok(res)
+        let mut res = self.program();
+        self.errors.append(&mut self.lexic.errors);
+        // Lexical errors are found ahead of the parser, the list goes in input order
+        self.errors.sort_by_key(AnalyzerError::position);
+        res.prefix = res.ast.prefix();
+        res.undefined = self
+            .warnings
+            .iter()
+            .map(|warning| warning.lexeme().to_string())
+            .collect();
+        res.warnings = self.warnings.clone();
+        res.symbols = self.symbols.clone();
+        res.errors = self.errors.clone();
+        res
     }
 
-    pub fn expr(&mut self) -> AnalyzerResult {
-        let term = self.term()?;
-        let mut res = self.rest_expr(&term)?;
+    /// `program → (stmt | ;)* EOF`, the lexer turns a line break into `;`
+    /// unless the line ends in an operator or is inside parentheses, so
+    /// `1 +⏎2` is one statement and `1⏎-2` are two
+    pub fn program(&mut self) -> Analyzed {
+        let start = self.position();
+        let mut res = Analyzed {
+            tree: TreeItem::new("program"),
+            ..Default::default()
+        };
+        let mut stmts = Vec::new();
+        loop {
+            let token = self.lexic.token.clone();
+            match token.token_type {
+                TokenType::EOF => break,
+                TokenType::Semicolon => {
+                    self.lexic.consume_token();
+                    res.tree
+                        .items
+                        .push(TreeItem::new(&type_to_string(&token.token_type)));
+                }
+                _ => {
+                    let start = self.position();
+                    let written = self.postfix.len();
+                    match self.stmt() {
+                        Ok(stmt) => {
+                            // Statements go one per line
+                            self.postfix.pop();
+                            self.postfix.push('\n');
+                            stmts.push(stmt.ast);
+                            res.result = stmt.result;
+                            res.value_type = stmt.value_type;
+                            res.tree.items.push(stmt.tree);
+                        }
+                        Err(error) => {
+                            self.postfix.truncate(written);
+                            self.report(error);
+                            let node = self.recover(&[TokenType::Semicolon]);
+                            res.tree.items.push(node);
+                            continue;
+                        }
+                    }
+                    // A token that can not start a statement, as a stray `)`, is
+                    // left where it was and has to be skipped to go on
+                    if self.position() == start {
+                        self.report(AnalyzerError::unexpected_token(
+                            &token,
+                            &[TokenType::Semicolon, TokenType::EOF],
+                        ));
+                        res.tree.items.push(TreeItem::new(&token.lexeme));
+                        self.lexic.consume_token();
+                        continue;
+                    }
+                    let next = self.lexic.token.clone();
+                    if next.token_type != TokenType::Semicolon && next.token_type != TokenType::EOF
+                    {
+                        self.report(AnalyzerError::unexpected_token(
+                            &next,
+                            &[TokenType::Semicolon, TokenType::EOF],
+                        ));
+                        let node = self.recover(&[TokenType::Semicolon]);
+                        res.tree.items.push(node);
+                    }
+                }
+            }
+        }
+        res.ast = Expr::Program(stmts);
+        self.postfix.pop();
+        res.postfix = std::mem::take(&mut self.postfix);
+        res.postfix_range = (0, res.postfix.len());
+        self.decorate(&mut res, start);
+        res.tree.attributes.value = res.result;
+        res
+    }
+
+    /// Parses a statement and evaluates its AST right away, so the following
+    /// statements are parsed against the updated variables and functions. A
+    /// statement with errors is not evaluated
+    pub fn stmt(&mut self) -> AnalyzerResult {
+        let start = self.position();
+        let errors = self.errors.len();
+        let is_assign = self.lexic.token.token_type == TokenType::Id
+            && self.lexic.peek_token().token_type == TokenType::Assign;
+        let is_declaration = matches!(
+            self.lexic.token.token_type,
+            TokenType::IntType | TokenType::FloatType
+        );
+        let mut res = if is_assign {
+            self.assign()?
+        } else if is_declaration {
+            self.declaration()?
+        } else if self.is_function_definition() {
+            self.function()?
+        } else {
+            self.conditional()?
+        };
         res.tree = TreeItem {
-            root: "expr".to_string(),
-            items: vec![term.tree, res.tree],
+            root: String::from("stmt"),
+            items: vec![res.tree],
+            ..Default::default()
         };
+        self.decorate(&mut res, start);
+        // The lexer is a token ahead, the errors past the statement are left
+        // for the next one
+        let end = self.lexic.previous_end;
+        let (inside, ahead): (Vec<_>, Vec<_>) = std::mem::take(&mut self.lexic.errors)
+            .into_iter()
+            .partition(|error| error.position() < end);
+        self.lexic.errors = ahead;
+        self.errors.extend(inside);
+        if self.errors.len() == errors {
+            self.calls = 0;
+            match self.evaluate(&res.ast) {
+                Ok(result) => res.result = result,
+                Err(error) => self.report(error),
+            }
+            fill_values(&mut res.tree, &self.values, &self.aliases);
+        }
         Ok(res)
     }
 
-    pub fn rest_expr(&mut self, analyzed: &Analyzed) -> AnalyzerResult {
-        let root = "rest_expr".to_string();
-        if let Some(token) = self.lexic.token.clone() {
-            return match token.token_type {
-                TokenType::Plus | TokenType::Minus => {
-                    self.lexic.consume_token();
-                    let term = self.term()?;
-                    let num = if let (Some(operand_a), Some(operand_b)) =
-                        (analyzed.result, term.result)
-                    {
-                        match token.token_type {
-                            TokenType::Plus => Some(operand_a + operand_b),
-                            TokenType::Minus => Some(operand_a - operand_b),
-                            _ => None,
-                        }
-                    } else {
-                        None
-                    };
-                    let mut partial = Analyzed {
-                        result: num,
-                        postfix: format!("{} {} {}", analyzed.postfix, term.postfix, token.lexeme),
-                        prefix: format!("{} {} {}", token.lexeme, analyzed.prefix, term.prefix),
-                        tree: TreeItem {
-                            root,
-                            items: vec![TreeItem::new(&token.lexeme), term.tree],
-                        },
-                    };
-                    let mut res = self.rest_expr(&partial)?;
-                    partial.tree.items.push(res.tree);
-                    res.tree = partial.tree;
-                    Ok(res)
+    pub fn assign(&mut self) -> AnalyzerResult {
+        let id = self.lexic.check_and_next(&TokenType::Id)?;
+        let assign = self.lexic.check_and_next(&TokenType::Assign)?;
+        let name = self.emit(&id.lexeme);
+        let value = self.conditional()?;
+        let symbol = self.symbols.declare(
+            &id.lexeme,
+            SymbolKind::Variable,
+            value.value_type,
+            Some(id.location),
+        );
+        symbol.initialized = true;
+        // A variable created by an assignment or bound in the variables panel
+        // takes the type of the values it is given, `x = 1; x = x / 2`
+        if !symbol.typed && !symbol.symbol_type.accepts(value.value_type) {
+            symbol.symbol_type = match (symbol.symbol_type, value.value_type) {
+                (old, new) if old.is_numeric() && new.is_numeric() => old.widen(new),
+                (_, new) => new,
+            };
+        }
+        let value_type = symbol.symbol_type;
+        if !value_type.accepts(value.value_type) && !value.ast.has_error() {
+            self.report(AnalyzerError::type_mismatch(
+                &id,
+                value_type,
+                value.value_type,
+            ));
+        }
+        let mut res = Analyzed {
+            value_type,
+            ast: Expr::Assign {
+                name: id.owned(),
+                value_type,
+                value: Box::new(value.ast),
+            },
+            tree: TreeItem {
+                root: String::from("assign"),
+                items: vec![
+                    leaf(&id, Some(value_type), Some(name)),
+                    TreeItem::new(&assign.lexeme),
+                    value.tree,
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        res.postfix_range = (name.0, self.emit("=").1);
+        self.decorate(&mut res, id.span.0);
+        Ok(res)
+    }
+
+    pub fn declaration(&mut self) -> AnalyzerResult {
+        let type_name = match self.lexic.token.clone() {
+            token if token.token_type == TokenType::IntType => {
+                self.lexic.check_and_next(&TokenType::IntType)?
+            }
+            _ => self.lexic.check_and_next(&TokenType::FloatType)?,
+        };
+        let declared_type = match type_name.token_type {
+            TokenType::IntType => Type::Int,
+            _ => Type::Float,
+        };
+        let id = self.lexic.check_and_next(&TokenType::Id)?;
+        if let Some(usage) = self
+            .undeclared
+            .iter()
+            .find(|usage| usage.lexeme == id.lexeme)
+        {
+            let error = AnalyzerError::use_before_declaration(usage, id.location);
+            self.report(error);
+        }
+        let mut redeclaration = None;
+        if let Some(symbol) = self.symbols.lookup_local(&id.lexeme) {
+            // Bindings from the variables panel have no position and may be redeclared
+            if let Some(previous) = symbol.declared_at {
+                redeclaration = Some(AnalyzerError::redeclaration(&id, previous));
+            }
+            symbol.declared_at = Some(id.location);
+            symbol.symbol_type = declared_type;
+        }
+        if let Some(error) = redeclaration {
+            self.report(error);
+        }
+        let symbol = self.symbols.declare(
+            &id.lexeme,
+            SymbolKind::Variable,
+            declared_type,
+            Some(id.location),
+        );
+        symbol.initialized = false;
+        symbol.typed = true;
+
+        let label = self.emit(&declaration_notation(declared_type, &id));
+        let name = (label.1 - id.lexeme.len(), label.1);
+        let mut items = vec![
+            TreeItem::new(&type_name.lexeme),
+            leaf(&id, Some(declared_type), Some(name)),
+        ];
+        let assign = match self.lexic.token.clone() {
+            token if token.token_type == TokenType::Assign => token,
+            _ => {
+                let mut res = Analyzed {
+                    value_type: declared_type,
+                    ast: Expr::Declaration {
+                        type_name: type_name.owned(),
+                        declared_type,
+                        name: id.owned(),
+                        value: None,
+                    },
+                    tree: TreeItem {
+                        root: String::from("declaration"),
+                        items,
+                        ..Default::default()
+                    },
+                    postfix_range: label,
+                    ..Default::default()
+                };
+                self.decorate(&mut res, type_name.span.0);
+                return Ok(res);
+            }
+        };
+        self.lexic.consume_token();
+        let value = self.conditional()?;
+        if !declared_type.accepts(value.value_type) && !value.ast.has_error() {
+            self.report(AnalyzerError::type_mismatch(
+                &id,
+                declared_type,
+                value.value_type,
+            ));
+        }
+        if let Some(symbol) = self.symbols.lookup_local(&id.lexeme) {
+            symbol.initialized = true;
+        }
+        items.push(TreeItem::new(&assign.lexeme));
+        items.push(value.tree);
+        let mut res = Analyzed {
+            value_type: declared_type,
+            ast: Expr::Declaration {
+                type_name: type_name.owned(),
+                declared_type,
+                name: id.owned(),
+                value: Some(Box::new(value.ast)),
+            },
+            tree: TreeItem {
+                root: String::from("declaration"),
+                items,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        res.postfix_range = (label.0, self.emit("=").1);
+        self.decorate(&mut res, type_name.span.0);
+        Ok(res)
+    }
+
+    pub fn function(&mut self) -> AnalyzerResult {
+        let id = self.lexic.check_and_next(&TokenType::Id)?;
+        let open = self.lexic.check_and_next(&TokenType::OpenParenthesis)?;
+        let mut params: Vec<Token> = Vec::new();
+        let mut items = vec![leaf(&id, None, None), TreeItem::new(&open.lexeme)];
+        if self.lexic.token.token_type != TokenType::ClosingParenthesis {
+            loop {
+                let param = self.lexic.check_and_next(&TokenType::Id)?;
+                if params.iter().any(|prev| prev.lexeme == param.lexeme) {
+                    self.report(AnalyzerError::duplicate_parameter(&param));
                 }
-                _ => {
-                    let mut res = analyzed.clone();
-                    res.tree = TreeItem {
-                        root,
-                        items: vec![TreeItem::new("ε")],
-                    };
-                    Ok(res)
+                items.push(leaf(&param, Some(Type::Float), None));
+                params.push(param);
+                match self.lexic.token.clone() {
+                    token if token.token_type == TokenType::Comma => {
+                        self.lexic.consume_token();
+                        items.push(TreeItem::new(&token.lexeme));
+                    }
+                    _ => break,
                 }
+            }
+        }
+        let close = self.lexic.check_and_next(&TokenType::ClosingParenthesis)?;
+        let assign = self.lexic.check_and_next(&TokenType::Assign)?;
+        items.push(TreeItem::new(&close.lexeme));
+        items.push(TreeItem::new(&assign.lexeme));
+        self.symbols.declare(
+            &id.lexeme,
+            SymbolKind::Function,
+            Type::Float,
+            Some(id.location),
+        );
+        self.symbols.enter_scope(&id.lexeme);
+
+        let params: Vec<String> = params
+            .into_iter()
+            .map(|param| {
+                self.symbols.declare(
+                    &param.lexeme,
+                    SymbolKind::Parameter,
+                    Type::Float,
+                    Some(param.location),
+                );
+                param.lexeme.to_string()
+            })
+            .collect();
+        // The name and the parameters are written in the label, `f(x, y)`
+        let label = self.emit(&function_notation(&id, &params));
+        items[0].attributes.postfix = Some((label.0, label.0 + id.lexeme.len()));
+        let mut offset = label.0 + id.lexeme.len() + 1;
+        let leaves = items
+            .iter_mut()
+            .filter(|item| item.attributes.span.is_some());
+        for (item, param) in leaves.skip(1).zip(params.iter()) {
+            item.attributes.postfix = Some((offset, offset + param.len()));
+            offset += param.len() + 2;
+        }
+        // Registered before parsing the body so that it may call itself, recursive
+        // calls are typed as float until the body type is known
+        let mut function = Function {
+            params: params.clone(),
+            return_type: Type::Float,
+            body: Rc::default(),
+        };
+        self.functions
+            .insert(id.lexeme.to_string(), function.clone());
+        let body = self.conditional();
+        self.symbols.exit_scope();
+        let body = body?;
+        let body_ast = Rc::new(body.ast);
+        function.return_type = body.value_type;
+        function.body = Rc::clone(&body_ast);
+        self.functions.insert(id.lexeme.to_string(), function);
+        if let Some(symbol) = self.symbols.lookup_function(&id.lexeme) {
+            symbol.symbol_type = body.value_type;
+        }
+        items[0].attributes.node_type = Some(body.value_type);
+        items.push(body.tree);
+
+        let mut res = Analyzed {
+            value_type: body.value_type,
+            ast: Expr::Function {
+                name: id.owned(),
+                params,
+                body: body_ast,
+            },
+            tree: TreeItem {
+                root: String::from("function"),
+                items,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        res.postfix_range = (label.0, self.emit("=").1);
+        self.decorate(&mut res, id.span.0);
+        Ok(res)
+    }
+
+    /// `if c then a else b` and `c ? a : b`, only the chosen branch is
+    /// evaluated
+    pub fn conditional(&mut self) -> AnalyzerResult {
+        let start = self.position();
+        let mut items = Vec::new();
+        let (keyword, condition) = match self.lexic.token.clone() {
+            token if token.token_type == TokenType::If => {
+                self.lexic.consume_token();
+                items.push(TreeItem::new(&token.lexeme));
+                let mut condition = self.conditional()?;
+                items.push(std::mem::take(&mut condition.tree));
+                items.push(self.expect(&TokenType::Then));
+                (token, condition)
+            }
+            _ => {
+                let mut condition = self.disjunction()?;
+                match self.lexic.token.clone() {
+                    token if token.token_type == TokenType::Question => {
+                        self.lexic.consume_token();
+                        items.push(std::mem::take(&mut condition.tree));
+                        items.push(TreeItem::new(&token.lexeme));
+                        (token, condition)
+                    }
+                    _ => return Ok(condition),
+                }
+            }
+        };
+        if condition.value_type != Type::Bool && !condition.ast.has_error() {
+            self.report(AnalyzerError::invalid_operand(
+                &keyword,
+                &[condition.value_type],
+            ));
+        }
+        let mut then = self.conditional()?;
+        items.push(std::mem::take(&mut then.tree));
+        let separator = match keyword.token_type {
+            TokenType::If => TokenType::Else,
+            _ => TokenType::Colon,
+        };
+        items.push(self.expect(&separator));
+        let mut otherwise = self.conditional()?;
+        items.push(std::mem::take(&mut otherwise.tree));
+
+        let value_type = match (then.value_type, otherwise.value_type) {
+            (Type::Bool, Type::Bool) => Type::Bool,
+            (a, b) if a.is_numeric() && b.is_numeric() => a.widen(b),
+            (a, b) => {
+                if !then.ast.has_error() && !otherwise.ast.has_error() {
+                    self.report(AnalyzerError::branch_mismatch(&keyword, a, b));
+                }
+                a
+            }
+        };
+        let mut res = Analyzed {
+            value_type,
+            ast: Expr::Conditional {
+                keyword: keyword.owned(),
+                condition: Box::new(condition.ast),
+                then: Box::new(then.ast),
+                otherwise: Box::new(otherwise.ast),
+                value_type,
+            },
+            tree: TreeItem {
+                root: String::from("conditional"),
+                items,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        res.postfix_range = (condition.postfix_range.0, self.emit(&res.ast.label()).1);
+        self.decorate(&mut res, start);
+        Ok(res)
+    }
+
+    pub fn disjunction(&mut self) -> AnalyzerResult {
+        self.chain("disjunction", Self::conjunction, Self::rest_disjunction)
+    }
+
+    pub fn rest_disjunction(&mut self, analyzed: Analyzed) -> AnalyzerResult {
+        self.rest(
+            "rest_disjunction",
+            &[TokenType::Or],
+            Self::conjunction,
+            analyzed,
+        )
+    }
+
+    pub fn conjunction(&mut self) -> AnalyzerResult {
+        self.chain("conjunction", Self::negation, Self::rest_conjunction)
+    }
+
+    pub fn rest_conjunction(&mut self, analyzed: Analyzed) -> AnalyzerResult {
+        self.rest(
+            "rest_conjunction",
+            &[TokenType::And],
+            Self::negation,
+            analyzed,
+        )
+    }
+
+    pub fn negation(&mut self) -> AnalyzerResult {
+        let token = self.lexic.token.clone();
+        if let TokenType::Not = token.token_type {
+            self.lexic.consume_token();
+            let operand = self.negation()?;
+            return self.unary_operation(String::from("negation"), token, operand);
+        }
+        self.comparison()
+    }
+
+    /// Comparisons do not associate, `a < b < c` is rejected
+    pub fn comparison(&mut self) -> AnalyzerResult {
+        let start = self.position();
+        let left = self.expr()?;
+        let token = self.lexic.token.clone();
+        if is_comparison(&token.token_type) {
+            self.lexic.consume_token();
+            let right = self.expr()?;
+            let mut res = Analyzed {
+                value_type: self.operation_type(&token, &left, &right),
+                ast: Expr::binary(token.owned(), left.ast, right.ast),
+                tree: TreeItem {
+                    root: String::from("comparison"),
+                    items: vec![left.tree, TreeItem::new(&token.lexeme), right.tree],
+                    ..Default::default()
+                },
+                ..Default::default()
             };
+            res.postfix_range = (left.postfix_range.0, self.emit(&res.ast.label()).1);
+            self.decorate(&mut res, start);
+            return Ok(res);
         }
-        Ok(analyzed.clone())
+        Ok(left)
+    }
+
+    pub fn expr(&mut self) -> AnalyzerResult {
+        self.chain("expr", Self::term, Self::rest_expr)
+    }
+
+    pub fn rest_expr(&mut self, analyzed: Analyzed) -> AnalyzerResult {
+        self.rest(
+            "rest_expr",
+            &[TokenType::Plus, TokenType::Minus],
+            Self::term,
+            analyzed,
+        )
     }
 
     pub fn term(&mut self) -> AnalyzerResult {
-        let factor = self.factor()?;
-        let mut res = self.rest_term(&factor)?;
+        self.chain("term", Self::unary, Self::rest_term)
+    }
+
+    pub fn rest_term(&mut self, analyzed: Analyzed) -> AnalyzerResult {
+        self.rest(
+            "rest_term",
+            &[
+                TokenType::Asterisk,
+                TokenType::Slash,
+                TokenType::Div,
+                TokenType::Percent,
+            ],
+            Self::unary,
+            analyzed,
+        )
+    }
+
+    /// `root → operand rest`, the operand is inherited by `rest`
+    fn chain(
+        &mut self,
+        root: &str,
+        operand: fn(&mut Self) -> AnalyzerResult,
+        rest: fn(&mut Self, Analyzed) -> AnalyzerResult,
+    ) -> AnalyzerResult {
+        let start = self.position();
+        let mut first = operand(self)?;
+        let tree = std::mem::take(&mut first.tree);
+        let mut res = rest(self, first)?;
         res.tree = TreeItem {
-            root: "term".to_string(),
-            items: vec![factor.tree, res.tree],
+            root: root.to_string(),
+            items: vec![tree, res.tree],
+            ..Default::default()
         };
+        self.decorate(&mut res, start);
         Ok(res)
     }
 
-    pub fn rest_term(&mut self, analyzed: &Analyzed) -> AnalyzerResult {
-        let root = String::from("rest_term");
-        if let Some(token) = self.lexic.token.clone() {
-            return match token.token_type {
-                TokenType::Asterisk | TokenType::Slash => {
-                    self.lexic.consume_token();
-                    let factor = self.factor()?;
-                    let num = if let (Some(operand_a), Some(operand_b)) =
-                        (analyzed.result, factor.result)
-                    {
-                        match token.token_type {
-                            TokenType::Asterisk => Some(operand_a * operand_b),
-                            TokenType::Slash => Some(operand_a / operand_b),
-                            _ => None,
-                        }
-                    } else {
-                        None
-                    };
-                    let mut partial = Analyzed {
-                        result: num,
-                        postfix: format!(
-                            "{} {} {}",
-                            analyzed.postfix, factor.postfix, token.lexeme
+    /// `root → operator operand root | ε`, associating to the left: the
+    /// inherited `analyzed` is the left operand of the next operator. The
+    /// recursion is a loop, each `root` node waits in `nodes` for the one
+    /// nested in it
+    fn rest(
+        &mut self,
+        root: &str,
+        operators: &[TokenType],
+        operand: fn(&mut Self) -> AnalyzerResult,
+        mut analyzed: Analyzed,
+    ) -> AnalyzerResult {
+        let start = analyzed.postfix_range.0;
+        let mut nodes = Vec::new();
+        loop {
+            let token = self.lexic.token.clone();
+            if !operators.contains(&token.token_type) {
+                break;
+            }
+            self.lexic.consume_token();
+            let right = operand(self)?;
+            let value_type = self.operation_type(&token, &analyzed, &right);
+            nodes.push(TreeItem {
+                root: root.to_string(),
+                items: vec![TreeItem::new(&token.lexeme), right.tree],
+                inherited: Some(synthesized(&analyzed)),
+                ..Default::default()
+            });
+            analyzed = Analyzed {
+                value_type,
+                ast: Expr::binary(token.owned(), analyzed.ast, right.ast),
+                ..Default::default()
+            };
+            analyzed.postfix_range = (start, self.emit(&analyzed.ast.label()).1);
+        }
+        let attributes = synthesized(&analyzed);
+        let mut tree = TreeItem {
+            root: root.to_string(),
+            items: vec![TreeItem::new("ε")],
+            attributes,
+            inherited: Some(attributes),
+        };
+        while let Some(mut node) = nodes.pop() {
+            node.items.push(tree);
+            node.attributes = attributes;
+            tree = node;
+        }
+        analyzed.tree = tree;
+        Ok(analyzed)
+    }
+
+    pub fn unary(&mut self) -> AnalyzerResult {
+        let token = self.lexic.token.clone();
+        if let TokenType::Plus | TokenType::Minus = token.token_type {
+            self.lexic.consume_token();
+            let operand = self.unary()?;
+            return self.unary_operation(String::from("unary"), token, operand);
+        }
+        self.power()
+    }
+
+    fn unary_operation(
+        &mut self,
+        root: String,
+        operator: Token,
+        operand: Analyzed,
+    ) -> AnalyzerResult {
+        let valid = match operator.token_type {
+            TokenType::Not => operand.value_type == Type::Bool,
+            _ => operand.value_type.is_numeric(),
+        };
+        if !valid && !operand.ast.has_error() {
+            self.report(AnalyzerError::invalid_operand(
+                &operator,
+                &[operand.value_type],
+            ));
+        }
+        let mut res = Analyzed {
+            value_type: operand.value_type,
+            ast: Expr::Unary {
+                operator: operator.owned(),
+                operand: Box::new(operand.ast),
+            },
+            tree: TreeItem {
+                root,
+                items: vec![TreeItem::new(&operator.lexeme), operand.tree],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        res.postfix_range = (operand.postfix_range.0, self.emit(&res.ast.label()).1);
+        self.decorate(&mut res, operator.span.0);
+        Ok(res)
+    }
+
+    pub fn power(&mut self) -> AnalyzerResult {
+        let start = self.position();
+        let base = self.factor()?;
+        let token = self.lexic.token.clone();
+        if let TokenType::Caret = token.token_type {
+            self.lexic.consume_token();
+            let exponent = self.unary()?;
+            let mut res = Analyzed {
+                value_type: self.operation_type(&token, &base, &exponent),
+                ast: Expr::binary(token.owned(), base.ast, exponent.ast),
+                tree: TreeItem {
+                    root: String::from("power"),
+                    items: vec![base.tree, TreeItem::new(&token.lexeme), exponent.tree],
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            res.postfix_range = (base.postfix_range.0, self.emit(&res.ast.label()).1);
+            self.decorate(&mut res, start);
+            return Ok(res);
+        }
+        Ok(base)
+    }
+
+    pub fn factor(&mut self) -> AnalyzerResult {
+        let root = String::from("factor");
+        let token = self.lexic.token.clone();
+        match token.token_type {
+            TokenType::OpenParenthesis => {
+                self.lexic.consume_token();
+                let mut analyzed = self.conditional()?;
+                let close = self.expect_closing(&token);
+                analyzed.tree = TreeItem {
+                    root,
+                    items: vec![TreeItem::new("("), analyzed.tree, close],
+                    ..Default::default()
+                };
+                self.decorate(&mut analyzed, token.span.0);
+                Ok(analyzed)
+            }
+            TokenType::Id if self.lexic.peek_token().token_type == TokenType::OpenParenthesis => {
+                let mut analyzed = self.call()?;
+                analyzed.tree = TreeItem {
+                    root,
+                    items: vec![analyzed.tree],
+                    ..Default::default()
+                };
+                self.decorate(&mut analyzed, token.span.0);
+                Ok(analyzed)
+            }
+            TokenType::Number | TokenType::Id => {
+                self.lexic.consume_token();
+                let (ast, value_type) = if let TokenType::Number = token.token_type {
+                    match Value::parse(&token.lexeme) {
+                        Some(value) => (
+                            Expr::Num {
+                                token: token.owned(),
+                                value,
+                            },
+                            value.value_type(),
                         ),
-                        prefix: format!("{} {} {} ", token.lexeme, analyzed.prefix, factor.prefix),
-                        tree: TreeItem {
-                            root,
-                            items: vec![TreeItem::new(&token.lexeme), factor.tree],
+                        None => {
+                            self.report(AnalyzerError::integer_overflow(&token));
+                            (Expr::Error(token.owned()), Type::Int)
+                        }
+                    }
+                } else if let Some(value) = self.constant(&token) {
+                    let value = Value::Float(value);
+                    (
+                        Expr::Num {
+                            token: token.owned(),
+                            value,
                         },
-                    };
-                    let mut res = self.rest_term(&partial)?;
-                    partial.tree.items.push(res.tree);
-                    res.tree = partial.tree;
-                    Ok(res)
+                        Type::Float,
+                    )
+                } else {
+                    (Expr::Var(token.owned()), self.lookup(&token))
+                };
+                let postfix = self.emit(&ast.label());
+                let mut res = Analyzed {
+                    value_type,
+                    ast,
+                    tree: TreeItem {
+                        root,
+                        items: vec![leaf(&token, Some(value_type), Some(postfix))],
+                        ..Default::default()
+                    },
+                    postfix_range: postfix,
+                    ..Default::default()
+                };
+                self.decorate(&mut res, token.span.0);
+                Ok(res)
+            }
+            _ => {
+                self.report(AnalyzerError::unexpected_token(
+                    &token,
+                    &[TokenType::OpenParenthesis, TokenType::Number, TokenType::Id],
+                ));
+                let mut tree = TreeItem::new("error");
+                if !SYNC.contains(&token.token_type) && token.token_type != TokenType::EOF {
+                    tree = self.recover(&SYNC);
                 }
-                _ => {
-                    let mut res = analyzed.clone();
-                    res.tree = TreeItem {
+                let ast = Expr::Error(token.owned());
+                let mut res = Analyzed {
+                    postfix_range: self.emit(&ast.label()),
+                    ast,
+                    tree: TreeItem {
                         root,
-                        items: vec![TreeItem::new("ε")],
-                    };
-                    Ok(res)
+                        items: vec![tree],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                self.decorate(&mut res, token.span.0);
+                Ok(res)
+            }
+        }
+    }
+
+    pub fn call(&mut self) -> AnalyzerResult {
+        let id = self.lexic.check_and_next(&TokenType::Id)?;
+        let open = self.lexic.check_and_next(&TokenType::OpenParenthesis)?;
+        let start = self.postfix.len();
+        let mut args: Vec<Analyzed> = Vec::new();
+        let mut items = vec![leaf(&id, None, None), TreeItem::new(&open.lexeme)];
+        if self.lexic.token.token_type != TokenType::ClosingParenthesis {
+            loop {
+                let mut arg = self.conditional()?;
+                items.push(std::mem::take(&mut arg.tree));
+                args.push(arg);
+                match self.lexic.token.clone() {
+                    token if token.token_type == TokenType::Comma => {
+                        self.lexic.consume_token();
+                        items.push(TreeItem::new(&token.lexeme));
+                    }
+                    _ => break,
+                }
+            }
+        }
+        let close = self.lexic.token.clone();
+        let closed = close.token_type == TokenType::ClosingParenthesis;
+        items.push(self.expect_closing(&open));
+        if let Some(arg) = args
+            .iter()
+            .find(|arg| !arg.value_type.is_numeric() && !arg.ast.has_error())
+        {
+            self.report(AnalyzerError::invalid_operand(&id, &[arg.value_type]));
+        }
+
+        if self.symbols.lookup_function(&id.lexeme).is_none() && find_builtin(&id.lexeme).is_some()
+        {
+            self.symbols
+                .declare_global(&id.lexeme, SymbolKind::Builtin, Type::Float, None);
+        }
+        if let Some(symbol) = self.symbols.lookup_function(&id.lexeme) {
+            symbol.used_at.push(id.location);
+        }
+        let function = self
+            .functions
+            .get(id.lexeme.as_ref())
+            .map(|function| (function.params.len(), function.return_type));
+        // A missing argument would be miscounted, `max(1,,2)` is a syntax error,
+        // and so are the arguments of a call left open as `max(1 2)`
+        let has_error = !closed || args.iter().any(|arg| arg.ast.has_error());
+        let value_type = if let Some((arity, return_type)) = function {
+            if arity != args.len() && !has_error {
+                self.report(AnalyzerError::wrong_arity(&id, arity, args.len()));
+            }
+            return_type
+        } else if let Some(builtin) = find_builtin(&id.lexeme) {
+            if builtin.arity != args.len() {
+                if !has_error {
+                    self.report(AnalyzerError::wrong_arity(&id, builtin.arity, args.len()));
+                }
+                Type::Float
+            } else {
+                let types: Vec<Type> = args.iter().map(|arg| arg.value_type).collect();
+                (builtin.result_type)(&types)
+            }
+        } else {
+            self.report(AnalyzerError::undefined_function(&id));
+            Type::Float
+        };
+        let ast = Expr::Call {
+            name: id.owned(),
+            args: args.into_iter().map(|arg| arg.ast).collect(),
+            close: close.owned(),
+        };
+        // The name is written after the arguments, with the arity: `max/2`
+        let notation = self.emit(&ast.label());
+        items[0].attributes.node_type = Some(value_type);
+        items[0].attributes.postfix = Some((notation.0, notation.0 + id.lexeme.len()));
+        let mut res = Analyzed {
+            value_type,
+            ast,
+            tree: TreeItem {
+                root: String::from("call"),
+                items,
+                ..Default::default()
+            },
+            postfix_range: (start, notation.1),
+            ..Default::default()
+        };
+        self.decorate(&mut res, id.span.0);
+        Ok(res)
+    }
+
+    /// Evaluates an AST node, `None` when it depends on a variable without
+    /// value. Values of top level nodes are kept by span to decorate the parse
+    /// tree
+    fn evaluate(&mut self, expr: &Expr) -> Result<Option<Value>, AnalyzerError> {
+        let value = match expr {
+            Expr::Num { value, .. } => Some(*value),
+            Expr::Var(id) => self.value_of(id),
+            Expr::Unary { operator, operand } => {
+                match (&operator.token_type, self.evaluate(operand)?) {
+                    (TokenType::Minus, Some(num)) => match negate(num) {
+                        Some(num) => Some(num),
+                        None => return Err(AnalyzerError::integer_overflow(operator)),
+                    },
+                    (TokenType::Not, Some(Value::Bool(value))) => Some(Value::Bool(!value)),
+                    (_, num) => num,
+                }
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+                value_type,
+                ..
+            } => {
+                let branch = match self.evaluate(condition)? {
+                    Some(Value::Bool(true)) => then,
+                    Some(_) => otherwise,
+                    None => return Ok(None),
+                };
+                self.evaluate(branch)?.map(|num| num.widen(*value_type))
+            }
+            Expr::Binary { .. } => self.evaluate_chain(expr)?,
+            Expr::Call { name, args, .. } => {
+                let mut values = Vec::new();
+                for arg in args.iter() {
+                    values.push(self.evaluate(arg)?);
+                }
+                match values.into_iter().collect::<Option<Vec<Value>>>() {
+                    Some(values) => self.call_function(name, values)?,
+                    None => None,
+                }
+            }
+            Expr::Assign {
+                name,
+                value_type,
+                value,
+            } => {
+                let num = self.evaluate(value)?;
+                self.bind(name, num.map(|num| num.widen(*value_type)))
+            }
+            Expr::Declaration {
+                declared_type,
+                name,
+                value,
+                ..
+            } => {
+                let num = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => None,
+                };
+                self.bind(name, num.map(|num| num.widen(*declared_type)))
+            }
+            Expr::Function { .. } | Expr::Error(_) => None,
+            Expr::Program(stmts) => {
+                let mut result = None;
+                for stmt in stmts.iter() {
+                    result = self.evaluate(stmt)?;
+                }
+                result
+            }
+        };
+        if let (0, Some(value)) = (self.depth, value) {
+            self.values.insert(expr.span(), value);
+        }
+        Ok(value)
+    }
+
+    /// Evaluates a binary operation and the ones nested in its left operand
+    /// in a loop, from the innermost one. A chain of left associative
+    /// operators nests as deep as it is long and would take a stack frame per
+    /// operator
+    fn evaluate_chain(&mut self, expr: &Expr) -> Result<Option<Value>, AnalyzerError> {
+        let mut chain = Vec::new();
+        let mut operand = expr;
+        while let Expr::Binary {
+            operator,
+            left,
+            right,
+            span,
+        } = operand
+        {
+            chain.push((operator, right, *span));
+            operand = left;
+        }
+        let mut value = self.evaluate(operand)?;
+        while let Some((operator, right, span)) = chain.pop() {
+            value = match operator.token_type {
+                TokenType::And | TokenType::Or => self.short_circuit(operator, value, right)?,
+                _ => {
+                    let right = self.evaluate(right)?;
+                    self.operate(operator, value, right)?
                 }
             };
+            if let (0, Some(value)) = (self.depth, value) {
+                self.values.insert(span, value);
+            }
         }
-        Ok(analyzed.clone())
+        Ok(value)
     }
 
-    pub fn factor(&mut self) -> AnalyzerResult {
-        let root = String::from("factor");
-        if let Some(token) = self.lexic.token.clone() {
-            return match token.token_type {
-                TokenType::OpenParenthesis => {
-                    self.lexic.consume_token();
-                    let mut analyzed = self.expr()?;
-                    self.lexic.check_and_next(&TokenType::ClosingParenthesis)?;
-                    analyzed.tree = TreeItem {
-                        root,
-                        items: vec![TreeItem::new("("), analyzed.tree, TreeItem::new(")")],
-                    };
-                    Ok(analyzed)
+    /// The right operand is only evaluated when the left one does not decide
+    /// the result, `None && false` is still false
+    fn short_circuit(
+        &mut self,
+        operator: &Token,
+        left: Option<Value>,
+        right: &Expr,
+    ) -> Result<Option<Value>, AnalyzerError> {
+        let decisive = operator.token_type == TokenType::Or;
+        Ok(match left {
+            Some(Value::Bool(value)) if value == decisive => Some(Value::Bool(decisive)),
+            left => match (left, self.evaluate(right)?) {
+                (_, Some(Value::Bool(value))) if value == decisive => Some(Value::Bool(decisive)),
+                (Some(_), right) => right,
+                (None, _) => None,
+            },
+        })
+    }
+
+    fn bind(&mut self, id: &Token, value: Option<Value>) -> Option<Value> {
+        match value {
+            Some(value) => {
+                self.env.insert(id.lexeme.to_string(), value);
+                if self.depth == 0 {
+                    self.values.insert(id.span, value);
                 }
-                TokenType::Number | TokenType::Id => {
-                    self.lexic.consume_token();
-                    let num = if let TokenType::Number = token.token_type {
-                        if let Ok(res) = token.lexeme.parse::<f32>() {
-                            Some(res)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-                    Ok(Analyzed {
-                        prefix: token.lexeme.clone(),
-                        postfix: token.lexeme.clone(),
-                        result: num,
-                        tree: TreeItem {
-                            root,
-                            items: vec![TreeItem {
-                                root: type_to_string(&token.token_type),
-                                items: vec![TreeItem::new(&token.lexeme)],
-                            }],
-                        },
-                    })
+            }
+            None => {
+                self.env.remove(id.lexeme.as_ref());
+            }
+        }
+        value
+    }
+
+    fn call_function(
+        &mut self,
+        id: &Token,
+        values: Vec<Value>,
+    ) -> Result<Option<Value>, AnalyzerError> {
+        let function = match self.functions.get(id.lexeme.as_ref()) {
+            Some(function) => function.clone(),
+            None => {
+                let builtin = match find_builtin(&id.lexeme) {
+                    Some(builtin) => builtin,
+                    None => return Err(AnalyzerError::undefined_function(id)),
+                };
+                return match (builtin.function)(&values) {
+                    Some(value) => Ok(Some(value)),
+                    None => Err(AnalyzerError::integer_overflow(id)),
+                };
+            }
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(AnalyzerError::recursion_limit(id, MAX_CALL_DEPTH));
+        }
+        if self.calls >= MAX_CALLS {
+            return Err(AnalyzerError::call_limit(id, MAX_CALLS));
+        }
+        self.calls += 1;
+        let scope = function
+            .params
+            .into_iter()
+            .zip(values)
+            .map(|(param, value)| (param, value.widen(Type::Float)))
+            .collect();
+        let scopes = std::mem::replace(&mut self.scopes, vec![scope]);
+        self.depth += 1;
+        let result = self.evaluate(&function.body);
+        self.depth -= 1;
+        self.scopes = scopes;
+        result
+    }
+
+    /// Type of a binary operation, an invalid one is reported and typed as the
+    /// operator would be with valid operands so the analysis may go on
+    fn operation_type(&mut self, operator: &Token, left: &Analyzed, right: &Analyzed) -> Type {
+        let (left_type, right_type) = (left.value_type, right.value_type);
+        if let Some(value_type) = binary_type(&operator.token_type, left_type, right_type) {
+            return value_type;
+        }
+        if !left.ast.has_error() && !right.ast.has_error() {
+            self.report(AnalyzerError::invalid_operand(
+                operator,
+                &[left_type, right_type],
+            ));
+        }
+        binary_type(&operator.token_type, Type::Bool, Type::Bool)
+            .or(binary_type(&operator.token_type, Type::Float, Type::Float))
+            .unwrap_or(Type::Float)
+    }
+
+    /// Keeps an error unless one of the same kind was reported at the same
+    /// position, which happens when recovering reports the same token twice.
+    /// A token out of place right after skipped characters is not reported
+    /// either, the lexical error already explains it
+    fn report(&mut self, error: AnalyzerError) {
+        if self.lexic.after_error
+            && *error.kind() == ErrorKind::UnexpectedToken
+            && error.position() == self.lexic.token.span.0
+        {
+            return;
+        }
+        if self.reported.insert((error.position(), error.code())) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Panic mode: skips tokens until one in `sync` or the end of the input,
+    /// the skipped lexemes hang from an `error` node
+    fn recover(&mut self, sync: &[TokenType]) -> TreeItem {
+        let mut tree = TreeItem::new("error");
+        loop {
+            let token = self.lexic.token.clone();
+            if token.token_type == TokenType::EOF || sync.contains(&token.token_type) {
+                break;
+            }
+            tree.items.push(TreeItem::new(&token.lexeme));
+            self.lexic.consume_token();
+        }
+        tree
+    }
+
+    /// Closes the parenthesis opened by `open`, reaching the end of the input
+    /// instead is reported as an unterminated parenthesis
+    fn expect_closing(&mut self, open: &Token) -> TreeItem {
+        if self.lexic.token.token_type == TokenType::EOF {
+            self.report(AnalyzerError::unterminated_parenthesis(open));
+            return TreeItem::new("error");
+        }
+        self.expect(&TokenType::ClosingParenthesis)
+    }
+
+    /// Consumes the expected token, a missing one is reported and left as an
+    /// `error` node without consuming anything
+    fn expect(&mut self, expected: &TokenType) -> TreeItem {
+        let token = self.lexic.token.clone();
+        if token.token_type != *expected {
+            self.report(AnalyzerError::unexpected_token(
+                &token,
+                std::slice::from_ref(expected),
+            ));
+            return TreeItem::new("error");
+        }
+        self.lexic.consume_token();
+        TreeItem::new(&token.lexeme)
+    }
+
+    fn position(&self) -> usize {
+        self.lexic.token.span.0
+    }
+
+    /// Appends the notation of a node to the postfix notation, returns its
+    /// byte range
+    fn emit(&mut self, notation: &str) -> (usize, usize) {
+        let start = self.postfix.len();
+        self.postfix.push_str(notation);
+        let end = self.postfix.len();
+        self.postfix.push(' ');
+        (start, end)
+    }
+
+    /// Stores the synthesized attributes of a production in the root of its
+    /// tree, spanning from `start` to the end of the last consumed token
+    fn decorate(&mut self, analyzed: &mut Analyzed, start: usize) {
+        let end = self.lexic.previous_end;
+        let span = if end > start {
+            Some((start, end))
+        } else {
+            None
+        };
+        if let Some(span) = span.filter(|span| *span != analyzed.ast.span()) {
+            self.aliases.entry(span).or_insert(analyzed.ast.span());
+        }
+        analyzed.tree.attributes = Attributes {
+            span,
+            ..synthesized(analyzed)
+        };
+    }
+
+    fn is_function_definition(&self) -> bool {
+        let mut lexic = self.lexic.cursor();
+        let mut expected = vec![TokenType::Id];
+        let mut previous: Option<TokenType> = None;
+        loop {
+            let token = lexic.token.clone();
+            if !expected.contains(&token.token_type) {
+                return false;
+            }
+            expected = match (previous, &token.token_type) {
+                (_, TokenType::Assign) => return true,
+                (None, TokenType::Id) => vec![TokenType::OpenParenthesis],
+                (_, TokenType::OpenParenthesis) => {
+                    vec![TokenType::Id, TokenType::ClosingParenthesis]
                 }
-                _ => Err(AnalyzerError::new(
-                    &token.lexeme,
-                    token.position + 1,
-                    "(, número o identificador",
-                )),
+                (_, TokenType::Id) => vec![TokenType::Comma, TokenType::ClosingParenthesis],
+                (_, TokenType::Comma) => vec![TokenType::Id],
+                (_, TokenType::ClosingParenthesis) => vec![TokenType::Assign],
+                _ => return false,
             };
+            previous = Some(token.token_type);
+            lexic.consume_token();
+        }
+    }
+
+    fn operate(
+        &self,
+        operator: &Token,
+        left: Option<Value>,
+        right: Option<Value>,
+    ) -> Result<Option<Value>, AnalyzerError> {
+        let (left, right) = match (left, right) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Ok(None),
+        };
+        if right.is_zero()
+            && matches!(
+                operator.token_type,
+                TokenType::Slash | TokenType::Div | TokenType::Percent
+            )
+        {
+            return Err(AnalyzerError::division_by_zero(operator));
+        }
+        match binary(&operator.token_type, left, right) {
+            Some(num) => Ok(Some(num)),
+            None => Err(AnalyzerError::integer_overflow(operator)),
+        }
+    }
+
+    fn lookup(&mut self, id: &Token<'a>) -> Type {
+        match self.symbols.lookup(&id.lexeme) {
+            Some(symbol) if !symbol.initialized => {
+                let symbol_type = symbol.symbol_type;
+                self.report(AnalyzerError::uninitialized(id));
+                symbol_type
+            }
+            Some(symbol) => {
+                symbol.used_at.push(id.location);
+                symbol.symbol_type
+            }
+            None => {
+                self.undeclared.push(id.clone());
+                self.env
+                    .get(id.lexeme.as_ref())
+                    .map_or(Type::Float, |value| value.value_type())
+            }
+        }
+    }
+
+    /// Value of a built-in constant, unless a variable, parameter or binding of
+    /// the same name hides it
+    fn constant(&mut self, id: &Token) -> Option<f32> {
+        let constant = find_constant(&id.lexeme)?;
+        if self.symbols.lookup(&id.lexeme).is_some() {
+            return None;
+        }
+        self.symbols
+            .declare_global(&id.lexeme, SymbolKind::Builtin, Type::Float, None)
+            .used_at
+            .push(id.location);
+        Some(constant.value)
+    }
+
+    fn value_of(&mut self, id: &Token) -> Option<Value> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(value) = scope.get(id.lexeme.as_ref()) {
+                return Some(*value);
+            }
+        }
+        let value = self.env.get(id.lexeme.as_ref()).copied();
+        if value.is_none()
+            && !self
+                .warnings
+                .iter()
+                .any(|warning| warning.lexeme() == id.lexeme)
+        {
+            self.warnings.push(AnalyzerError::undefined_variable(id));
+        }
+        value
+    }
+}
+
+/// Attributes synthesized from the AST of a production, the value is filled
+/// in once the statement is evaluated
+fn synthesized(analyzed: &Analyzed) -> Attributes {
+    Attributes {
+        node_type: Some(analyzed.value_type),
+        value: None,
+        postfix: Some(analyzed.postfix_range),
+        span: Some(analyzed.ast.span()),
+    }
+}
+
+fn leaf(token: &Token, node_type: Option<Type>, postfix: Option<(usize, usize)>) -> TreeItem {
+    TreeItem {
+        root: type_to_string(&token.token_type),
+        items: vec![TreeItem::new(&token.lexeme)],
+        attributes: Attributes {
+            node_type,
+            value: None,
+            postfix,
+            span: Some(token.span),
+        },
+        inherited: None,
+    }
+}
+
+/// Copies the evaluated values into the parse tree by span. A node that spans
+/// more input than its AST node, like a parenthesized factor, is looked up by
+/// the span of the AST node. The nodes left to fill are kept in a stack, the
+/// `rest` nodes of a chain nest as deep as the chain is long
+fn fill_values(
+    tree: &mut TreeItem,
+    values: &BTreeMap<Span, Value>,
+    aliases: &BTreeMap<Span, Span>,
+) {
+    let mut pending = vec![tree];
+    while let Some(tree) = pending.pop() {
+        let attributes = std::iter::once(&mut tree.attributes).chain(tree.inherited.as_mut());
+        for attributes in attributes {
+            if let Some(span) = attributes.span {
+                let span = aliases.get(&span).unwrap_or(&span);
+                attributes.value = values.get(span).copied();
+            }
+        }
+        pending.extend(tree.items.iter_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::{super::lexic::Location, *};
+
+    fn analyze(input: &str) -> Analyzed {
+        Analyzer::new(input, &Default::default()).analyze()
+    }
+
+    fn codes(input: &str) -> Vec<&'static str> {
+        analyze(input)
+            .errors
+            .iter()
+            .map(AnalyzerError::code)
+            .collect()
+    }
+
+    #[test]
+    fn declarations_are_checked_where_the_name_is() {
+        let errors = analyze("int x = 1; float x").errors;
+        assert_eq!(
+            errors.iter().map(AnalyzerError::code).collect::<Vec<_>>(),
+            ["E0305"]
+        );
+        assert_eq!(errors[0].position(), 17);
+        assert_eq!(
+            errors[0].kind(),
+            &ErrorKind::Redeclaration {
+                previous: Location { line: 1, column: 5 }
+            }
+        );
+
+        let errors = analyze("x + 1; int x = 2").errors;
+        assert_eq!(
+            errors.iter().map(AnalyzerError::code).collect::<Vec<_>>(),
+            ["E0306"]
+        );
+        assert_eq!(errors[0].position(), 0);
+
+        let errors = analyze("int x; x + 1").errors;
+        assert_eq!(
+            errors.iter().map(AnalyzerError::code).collect::<Vec<_>>(),
+            ["E0307"]
+        );
+        assert_eq!(errors[0].position(), 7);
+
+        assert!(codes("int x; x = 2; x + 1").is_empty());
+        assert!(codes("int x = 1; f(x) = x + 1; f(x)").is_empty());
+        let env = Environment::from([(String::from("n"), Value::Int(3))]);
+        assert!(Analyzer::new("float n = 1.5; n", &env)
+            .analyze()
+            .errors
+            .is_empty());
+    }
+
+    #[test]
+    fn unary_operators() {
+        for (input, postfix, result) in [
+            ("-(1 + 2)", "1 2 + neg", Value::Int(-3)),
+            ("+-2", "2 neg pos", Value::Int(-2)),
+            ("- -2", "2 neg neg", Value::Int(2)),
+            ("3 * -2", "3 2 neg *", Value::Int(-6)),
+        ] {
+            let res = analyze(input);
+            assert!(res.errors.is_empty(), "{:?}", input);
+            assert_eq!(res.postfix, postfix, "{:?}", input);
+            assert_eq!(res.result, Some(result), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn exponent_is_right_associative_and_binds_tighter() {
+        for (input, postfix, result) in [
+            ("-2^2", "2 2 ^ neg", Value::Float(-4.0)),
+            ("2^3^2", "2 3 2 ^ ^", Value::Float(512.0)),
+            ("2 * 3^2", "2 3 2 ^ *", Value::Float(18.0)),
+        ] {
+            let res = analyze(input);
+            assert!(res.errors.is_empty(), "{:?}", input);
+            assert_eq!(res.postfix, postfix, "{:?}", input);
+            assert_eq!(res.result, Some(result), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn division_and_remainder_round_toward_negative_infinity() {
+        for (input, postfix, result) in [
+            ("7 div -2", "7 2 neg div", Value::Int(-4)),
+            ("-7 % 2", "7 neg 2 %", Value::Int(1)),
+            ("10 - 4 - 3", "10 4 - 3 -", Value::Int(3)),
+            ("1 + 2 * 3", "1 2 3 * +", Value::Int(7)),
+        ] {
+            let res = analyze(input);
+            assert!(res.errors.is_empty(), "{:?}", input);
+            assert_eq!(res.postfix, postfix, "{:?}", input);
+            assert_eq!(res.result, Some(result), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn int_remainder_does_not_overflow() {
+        let res = analyze("(-9223372036854775807 - 1) % 3");
+        assert_eq!(res.result, Some(Value::Int(1)));
+        assert_eq!(
+            analyze("-5 % 9223372036854775807").result,
+            Some(Value::Int(9223372036854775802))
+        );
+        assert_eq!(codes("(-9223372036854775807 - 1) % -1"), ["E0310"]);
+    }
+
+    #[test]
+    fn literals_out_of_range_overflow() {
+        for input in ["9223372036854775808", "1e400", "3.5e38", "1.0E39"] {
+            assert_eq!(codes(input), ["E0310"], "{:?}", input);
+        }
+        assert_eq!(analyze("3.4e38").result, Some(Value::Float(3.4e38)));
+        assert_eq!(analyze("1e-50").result, Some(Value::Float(0.0)));
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        let res = analyze("1 > 2 && 1 / 0 > 0");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Bool(false)));
+        assert_eq!(res.value_type, Type::Bool);
+        assert_eq!(res.postfix, "1 2 > 1 0 / 0 > &&");
+        assert_eq!(
+            analyze("1 < 2 || 1 / 0 > 0").result,
+            Some(Value::Bool(true))
+        );
+        assert_eq!(codes("1 < 2 && 1 / 0 > 0"), ["E0401"]);
+        assert_eq!(
+            analyze("!(1 > 2) && 1 != 2").result,
+            Some(Value::Bool(true))
+        );
+        assert_eq!(codes("1 && 1 < 2"), ["E0308"]);
+    }
+
+    #[test]
+    fn only_the_chosen_branch_is_evaluated() {
+        let res = analyze("if 1 > 0 then 1 else 1 / 0");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Float(1.0)));
+        assert_eq!(res.postfix, "1 0 > 1 1 0 / if");
+        assert_eq!(res.prefix, "if > 1 0 1 / 1 0");
+        assert_eq!(analyze("1 < 0 ? 1 / 0 : 2").result, Some(Value::Float(2.0)));
+        assert_eq!(codes("1 < 0 ? 1 : 1 / 0"), ["E0401"]);
+        assert_eq!(
+            analyze("f(n) = if n < 2 then 1 else n * f(n - 1); f(5)").result,
+            Some(Value::Float(120.0))
+        );
+        assert_eq!(codes("if 1 > 0 then 1 else 2 < 3"), ["E0309"]);
+        assert_eq!(codes("if 1 then 1 else 2"), ["E0308"]);
+    }
+
+    #[test]
+    fn variables_with_no_value_are_warnings() {
+        let res = analyze("x + 1; y * x");
+        assert!(res.errors.is_empty());
+        let warnings: Vec<_> = res
+            .warnings
+            .iter()
+            .map(|warning| (warning.code(), warning.lexeme(), warning.position()))
+            .collect();
+        assert_eq!(warnings, [("E0311", "x", 0), ("E0311", "y", 7)]);
+        assert_eq!(res.undefined, ["x", "y"]);
+        assert!(!res.warnings[0].kind().is_fatal());
+
+        let res = analyze("x = 2; x + 1");
+        assert!(res.warnings.is_empty());
+        assert_eq!(res.result, Some(Value::Int(3)));
+    }
+
+    #[test]
+    fn assigned_variables_widen_their_type() {
+        let res = analyze("x = 1; x = x / 2");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Float(0.5)));
+        let env = Environment::from([(String::from("n"), Value::Int(3))]);
+        let res = Analyzer::new("n = n / 2", &env).analyze();
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Float(1.5)));
+        assert_eq!(codes("int x = 1; x = x / 2"), ["E0304"]);
+        assert_eq!(codes("float y; y = 1 < 2"), ["E0304"]);
+    }
+
+    #[test]
+    fn builtins_keep_int_arguments_exact() {
+        assert_eq!(analyze("abs(16777217)").result, Some(Value::Int(16777217)));
+        assert_eq!(
+            analyze("max(9007199254740993, 1)").result,
+            Some(Value::Int(9007199254740993))
+        );
+        for input in [
+            "floor(1e30)",
+            "ceil(sqrt(-1))",
+            "abs(-9223372036854775807 - 1)",
+        ] {
+            assert_eq!(codes(input), ["E0310"], "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn malformed_arguments_are_not_counted() {
+        assert_eq!(codes("max(1,,2)"), ["E0201"]);
+        assert_eq!(codes("f(x) = x; f(1 +, 2)"), ["E0201"]);
+    }
+
+    #[test]
+    fn calls_are_limited_in_depth_and_in_total() {
+        let twice = "f(n) = if n <= 0 then 0 else f(n - 1) + f(n - 1); ";
+        assert_eq!(
+            analyze(&format!("{}f(10)", twice)).result,
+            Some(Value::Float(0.0))
+        );
+        let res = analyze(&format!("{}f(30)", twice));
+        assert_eq!(
+            *res.errors[0].kind(),
+            ErrorKind::RecursionLimit {
+                limit: MAX_CALLS,
+                nested: false
+            }
+        );
+        assert_eq!(codes("g(n) = g(n + 1); g(0)"), ["E0402"]);
+    }
+
+    #[test]
+    fn errors_of_another_kind_at_the_same_position_are_kept() {
+        assert_eq!(codes("int x = 1; int x = 2.5"), ["E0305", "E0304"]);
+        assert_eq!(codes("max(1 2)"), ["E0201"]);
+        assert_eq!(codes("f(x) = x; f(1 2) + 1"), ["E0201"]);
+    }
+
+    #[test]
+    fn empty_input_has_no_statements() {
+        for input in ["", " \n\t", "\n;\n"] {
+            let res = analyze(input);
+            assert!(res.errors.is_empty(), "{:?}", input);
+            assert_eq!(res.result, None);
+            assert_eq!(res.postfix, "");
+            assert_eq!(res.prefix, "");
+        }
+    }
+
+    #[test]
+    fn unicode_names_and_glyphs_evaluate() {
+        let res = analyze("año = 4; año × 3 ÷ 2 − 1");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Float(5.0)));
+        assert_eq!(res.postfix, "año 4 =\naño 3 * 2 / 1 -");
+        assert_eq!(
+            analyze("2 × π").result,
+            Some(Value::Float(std::f32::consts::TAU))
+        );
+        // The constants are only defaults, a variable may take their name
+        assert_eq!(analyze("π = 3; π").result, Some(Value::Int(3)));
+    }
+
+    #[test]
+    fn tokens_that_start_no_statement_are_skipped() {
+        for input in [")", ",", "then", "else", ":", "1\n)", "1 + )", "x = 1; ) 2"] {
+            assert_eq!(codes(input), ["E0201"], "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn long_chains_are_parsed_in_a_loop() {
+        let input = format!("0{}", " + 1".repeat(200));
+        let res = analyze(&input);
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Int(200)));
+        assert!(res.postfix.starts_with("0 1 + 1 +"));
+    }
+
+    /// Eight times the terms take about eight times as long, a chain whose
+    /// nodes copied the notation of their subtree would take over twenty
+    #[test]
+    fn long_chains_take_linear_time() {
+        let time = |terms: usize| {
+            let input = format!("1{}", " + x * 2".repeat(terms));
+            (0..3)
+                .map(|_| {
+                    let start = Instant::now();
+                    analyze(&input);
+                    start.elapsed().as_secs_f64()
+                })
+                .fold(f64::MAX, f64::min)
+        };
+        let ratio = time(16000) / time(2000);
+        assert!(ratio < 16.0, "{}", ratio);
+    }
+
+    /// Runs in the stack of a test thread, the chains are walked in loops
+    #[test]
+    fn long_chains_take_no_stack_frame_per_operator() {
+        let res = analyze(&format!("0{}", " + 1".repeat(50_000)));
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Int(50_000)));
+        assert!(res.prefix.starts_with("+ + +"));
+        assert_eq!(res.ast.tree().items.len(), 1);
+        let res = analyze(&format!("0 > 1{}", " || 0 > 1".repeat(50_000)));
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Bool(false)));
+    }
+
+    #[test]
+    fn lexical_errors_ahead_belong_to_the_next_statement() {
+        let res = analyze("x = 1 #\ny = 2");
+        assert_eq!(codes("x = 1 #\ny = 2"), ["E0101"]);
+        assert_eq!(res.tree.items[0].attributes.value, Some(Value::Int(1)));
+    }
+
+    #[test]
+    fn line_breaks_end_statements() {
+        let res = analyze("x = 1\n-x");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.postfix, "x 1 =\nx neg");
+        assert_eq!(res.result, Some(Value::Int(-1)));
+        let res = analyze("a = 2\nb = a\n(b)");
+        assert!(res.errors.is_empty());
+        assert_eq!(res.result, Some(Value::Int(2)));
+    }
+
+    #[test]
+    fn expressions_go_on_after_operators_and_in_parentheses() {
+        for input in ["1 +\n2", "(1\n+ 2)", "max(1,\n2) + 1", "// c\n\n3 // d\n"] {
+            let res = analyze(input);
+            assert!(res.errors.is_empty(), "{:?}", input);
+            assert_eq!(res.result, Some(Value::Int(3)), "{:?}", input);
         }
-        Err(AnalyzerError::new(
-            &String::from(self.lexic.current),
-            self.lexic.pos,
-            "",
-        ))
     }
 }
@@ -0,0 +1,212 @@
+use std::{cmp::Ordering, fmt};
+
+use super::lexic::TokenType;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Type {
+    Int,
+    #[default]
+    Float,
+    Bool,
+}
+
+impl Type {
+    pub fn widen(self, other: Type) -> Type {
+        match (self, other) {
+            (Type::Int, Type::Int) => Type::Int,
+            _ => Type::Float,
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(self, Type::Int | Type::Float)
+    }
+
+    pub fn accepts(self, other: Type) -> bool {
+        self == other || (self == Type::Float && other == Type::Int)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Bool => write!(f, "bool"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f32),
+    Bool(bool),
+}
+
+impl Value {
+    /// Value of a number literal, `None` when it does not fit its type
+    pub fn parse(lexeme: &str) -> Option<Value> {
+        if lexeme.contains(['.', 'e', 'E']) {
+            return lexeme
+                .parse::<f32>()
+                .ok()
+                .filter(|num| num.is_finite())
+                .map(Value::Float);
+        }
+        lexeme.parse::<i64>().ok().map(Value::Int)
+    }
+
+    pub fn value_type(&self) -> Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Float(_) => Type::Float,
+            Value::Bool(_) => Type::Bool,
+        }
+    }
+
+    pub fn as_float(&self) -> f32 {
+        match self {
+            Value::Int(num) => *num as f32,
+            Value::Float(num) => *num,
+            Value::Bool(value) => f32::from(u8::from(*value)),
+        }
+    }
+
+    pub fn widen(self, to: Type) -> Value {
+        match (self, to) {
+            (Value::Int(num), Type::Float) => Value::Float(num as f32),
+            _ => self,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Int(num) => *num == 0,
+            Value::Float(num) => *num == 0.0,
+            Value::Bool(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(num) => write!(f, "{}", num),
+            Value::Float(num) => write!(f, "{:?}", num),
+            Value::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Type of `left operator right`, `None` when the operator does not accept
+/// the operands. Comparisons produce a bool, `/` and `^` always produce a
+/// float and the remaining arithmetic operators keep `int` only when both
+/// operands are `int`
+pub fn binary_type(operator: &TokenType, left: Type, right: Type) -> Option<Type> {
+    let booleans = left == Type::Bool && right == Type::Bool;
+    match operator {
+        TokenType::And | TokenType::Or => booleans.then_some(Type::Bool),
+        TokenType::Equal | TokenType::NotEqual if booleans => Some(Type::Bool),
+        _ if !left.is_numeric() || !right.is_numeric() => None,
+        _ if is_comparison(operator) => Some(Type::Bool),
+        TokenType::Slash | TokenType::Caret => Some(Type::Float),
+        _ => Some(left.widen(right)),
+    }
+}
+
+pub fn is_comparison(operator: &TokenType) -> bool {
+    matches!(
+        operator,
+        TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Equal
+            | TokenType::NotEqual
+    )
+}
+
+/// Evaluates `left operator right`, returns `None` when an `int` operation
+/// overflows. `div` and `%` use floored division, so `a = b * (a div b) + a % b`
+/// and the remainder takes the sign of the divisor
+pub fn binary(operator: &TokenType, left: Value, right: Value) -> Option<Value> {
+    if is_comparison(operator) {
+        return Some(Value::Bool(compare(operator, left, right)));
+    }
+    if let (Value::Bool(a), Value::Bool(b)) = (left, right) {
+        return match operator {
+            TokenType::And => Some(Value::Bool(a && b)),
+            TokenType::Or => Some(Value::Bool(a || b)),
+            _ => None,
+        };
+    }
+    if binary_type(operator, left.value_type(), right.value_type()) == Some(Type::Int) {
+        let (a, b) = match (left, right) {
+            (Value::Int(a), Value::Int(b)) => (a, b),
+            _ => return None,
+        };
+        let floored = || {
+            let quotient = a.checked_div(b)?;
+            if a % b != 0 && (a < 0) != (b < 0) {
+                return Some(quotient - 1);
+            }
+            Some(quotient)
+        };
+        return match operator {
+            TokenType::Plus => a.checked_add(b),
+            TokenType::Minus => a.checked_sub(b),
+            TokenType::Asterisk => a.checked_mul(b),
+            TokenType::Div => floored(),
+            TokenType::Percent => a.checked_rem(b).map(|remainder| {
+                if remainder != 0 && (remainder < 0) != (b < 0) {
+                    remainder + b
+                } else {
+                    remainder
+                }
+            }),
+            _ => None,
+        }
+        .map(Value::Int);
+    }
+    let (a, b) = (left.as_float(), right.as_float());
+    let num = match operator {
+        TokenType::Plus => a + b,
+        TokenType::Minus => a - b,
+        TokenType::Asterisk => a * b,
+        TokenType::Slash => a / b,
+        TokenType::Div => (a / b).floor(),
+        TokenType::Percent => a - b * (a / b).floor(),
+        TokenType::Caret => a.powf(b),
+        _ => return None,
+    };
+    Some(Value::Float(num))
+}
+
+/// `int` operands are compared exactly, a comparison involving `NaN` is
+/// only true for `!=`
+fn compare(operator: &TokenType, left: Value, right: Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Int(a), Value::Int(b)) => a.partial_cmp(&b),
+        (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(&b),
+        _ => left.as_float().partial_cmp(&right.as_float()),
+    };
+    match operator {
+        TokenType::Less => ordering == Some(Ordering::Less),
+        TokenType::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        TokenType::Greater => ordering == Some(Ordering::Greater),
+        TokenType::GreaterEqual => {
+            matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+        }
+        TokenType::Equal => ordering == Some(Ordering::Equal),
+        _ => ordering != Some(Ordering::Equal),
+    }
+}
+
+pub fn negate(value: Value) -> Option<Value> {
+    match value {
+        Value::Int(num) => num.checked_neg().map(Value::Int),
+        Value::Float(num) => Some(Value::Float(-num)),
+        Value::Bool(_) => None,
+    }
+}
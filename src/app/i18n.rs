@@ -0,0 +1,373 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Language {
+    #[default]
+    Spanish,
+    English,
+}
+
+/// Language of every message, shared by the analyzer errors and the interface
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Spanish, Language::English];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Spanish => "es",
+            Language::English => "en",
+        }
+    }
+
+    /// Accepts a language code, `es` or `en`, or a locale like `en_US.UTF-8`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.split(['_', '.', '-']).next().unwrap_or_default();
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code().eq_ignore_ascii_case(code))
+    }
+
+    pub fn next(self) -> Self {
+        let index = Language::ALL
+            .iter()
+            .position(|language| *language == self)
+            .unwrap_or(0);
+        Language::ALL[(index + 1) % Language::ALL.len()]
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::Spanish => SPANISH,
+            Language::English => ENGLISH,
+        }
+    }
+}
+
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|other| *other == language)
+        .unwrap_or(0);
+    LANGUAGE.store(index as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed) as usize]
+}
+
+/// Message `key` in the current language, `{0}`, `{1}`, ... are replaced by
+/// `args`. A missing key is shown as is
+pub fn tr(key: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut message = text(key).to_string();
+    for (index, arg) in args.iter().enumerate() {
+        message = message.replace(&format!("{{{}}}", index), &arg.to_string());
+    }
+    message
+}
+
+/// Message `key` in the current language, for messages without arguments
+pub fn text(key: &str) -> &str {
+    language()
+        .catalog()
+        .iter()
+        .find(|(other, _)| *other == key)
+        .map_or(key, |(_, message)| message)
+}
+
+const SPANISH: &[(&str, &str)] = &[
+    ("ui.input", "Entrada"),
+    ("ui.result", "Resultado"),
+    ("ui.variables", "Variables"),
+    ("ui.tab.tree", "Árbol"),
+    ("ui.tab.ast", "Árbol abstracto"),
+    ("ui.tab.symbols", "Símbolos"),
+    ("ui.tab.tokens", "Tokens"),
+    (
+        "ui.help.input",
+        "Presione ESC para salir, TAB para editar variables, F2 para cambiar de vista, F3 para mostrar atributos, F4 para cambiar de idioma",
+    ),
+    (
+        "ui.help.variables",
+        "ESC: salir, TAB: entrada, F2: vista, F3: atributos, F4: idioma, Enter: guardar 'nombre = valor', ↑/↓: editar, Supr: eliminar",
+    ),
+    ("ui.postfix", "Posfijo: {0}"),
+    ("ui.prefix", "Prefijo: {0}"),
+    ("ui.value", "Resultado: {0}"),
+    ("ui.undefined", "Sin valor: {0}"),
+    ("attribute.type", "tipo"),
+    ("attribute.value", "valor"),
+    ("attribute.postfix", "posfijo"),
+    ("attribute.span", "pos"),
+    ("attribute.inherited", "heredado"),
+    ("symbols.name", "Nombre"),
+    ("symbols.kind", "Clase"),
+    ("symbols.type", "Tipo"),
+    ("symbols.scope", "Ámbito"),
+    ("symbols.declared", "Declarado"),
+    ("symbols.uses", "Usos"),
+    ("tokens.lexeme", "Lexema"),
+    ("tokens.type", "Tipo"),
+    ("tokens.location", "Posición"),
+    ("symbol.variable", "variable"),
+    ("symbol.parameter", "parámetro"),
+    ("symbol.function", "función"),
+    ("symbol.builtin", "predefinida"),
+    ("trivia.line_comment", "comentario de línea"),
+    ("trivia.block_comment", "comentario de bloque"),
+    ("binding.expected", "Se esperaba: 'nombre = valor'"),
+    ("binding.missing_name", "Se esperaba un identificador antes de '='"),
+    ("binding.invalid_name", "'{0}' no es un identificador válido"),
+    ("binding.missing_value", "Se esperaba un valor después de '='"),
+    ("binding.not_numeric", "El valor de '{0}' no es numérico"),
+    ("cli.missing_language", "Falta el idioma después de '--lang'"),
+    ("cli.unknown_language", "Idioma desconocido '{0}', use 'es' o 'en'"),
+    ("cli.unknown_option", "Opción desconocida '{0}', uso: analizador-sin [--lang es|en]"),
+    ("token.number", "número"),
+    ("token.id", "identificador"),
+    ("token.eof", "Final de archivo"),
+    ("token.newline", "salto de línea"),
+    ("list.or", "{0} o {1}"),
+    ("list.and", "{0} y {1}"),
+    ("diagnostic.help", "ayuda"),
+    ("error.E0101", "Caracter inesperado '{0}', en {1}"),
+    ("error.E0102", "Número mal formado '{0}', en {1}, se esperaba un dígito"),
+    ("error.E0103", "Número mal formado '{0}', en {1}, se esperaba un operador"),
+    ("error.E0104", "El comentario abierto en {1} no se cierra"),
+    ("error.E0201", "Símbolo inesperado '{0}', en {1}, se esperaba: {2}"),
+    ("error.E0202", "El paréntesis abierto en {1} no se cierra"),
+    ("error.E0301", "Función no definida '{0}', en {1}"),
+    (
+        "error.E0302",
+        "La función '{0}', en {1}, espera {2} argumento(s) y recibió {3}",
+    ),
+    ("error.E0303", "Parámetro duplicado '{0}', en {1}"),
+    (
+        "error.E0304",
+        "Tipos incompatibles: '{0}', en {1}, es de tipo {2} y se le asigna un valor {3}",
+    ),
+    (
+        "error.E0305",
+        "La variable '{0}', en {1}, ya fue declarada en {2}",
+    ),
+    (
+        "error.E0306",
+        "La variable '{0}' se usa en {1} antes de su declaración en {2}",
+    ),
+    (
+        "error.E0307",
+        "La variable '{0}', en {1}, se usa sin haber sido inicializada",
+    ),
+    (
+        "error.E0308",
+        "Operación inválida: '{0}', en {1}, no admite operandos de tipo {2}",
+    ),
+    (
+        "error.E0309",
+        "Las ramas de '{0}', en {1}, son de tipos distintos: {2} y {3}",
+    ),
+    ("error.E0310", "Desbordamiento numérico en '{0}', en {1}"),
+    ("error.E0311", "Variable sin valor '{0}', en {1}"),
+    ("error.E0401", "División entre cero en el operador '{0}', en {1}"),
+    (
+        "error.E0402",
+        "La llamada a '{0}', en {1}, excede el límite de {2} llamadas anidadas",
+    ),
+    (
+        "error.E0402.calls",
+        "La llamada a '{0}', en {1}, excede el límite de {2} llamadas por sentencia",
+    ),
+    ("hint.unterminated_parenthesis", "¿Olvidó cerrar el paréntesis con ')'?"),
+    ("hint.unterminated_comment", "Cierre el comentario con '*/', los comentarios pueden anidarse"),
+    ("hint.unexpected_end", "La entrada termina antes de completar la expresión"),
+    ("hint.separator", "Separe las sentencias con ';' o con un salto de línea"),
+    ("hint.missing_digit", "Agregue un dígito, por ejemplo '{0}0'"),
+    ("hint.undefined_function", "Las funciones se definen con 'nombre(parámetros) = expresión'"),
+    ("hint.undefined_variable", "Asigne un valor con '{0} = valor' o en el panel de variables"),
+    ("hint.int_from_float", "Declare la variable como 'float' o use 'div' para dividir enteros"),
+    ("hint.redeclaration", "Para cambiar su valor use una asignación: '{0} = valor'"),
+    ("hint.use_before_declaration", "Mueva la declaración antes del primer uso"),
+    ("hint.uninitialized", "Asigne un valor a '{0}' antes de usarla"),
+    ("hint.branch_mismatch", "Ambas ramas deben ser bool o ambas numéricas"),
+    ("hint.recursion_limit", "Revise que la función tenga un caso base"),
+    ("hint.call_limit", "Cada caso que se llama más de una vez multiplica las llamadas"),
+];
+
+const ENGLISH: &[(&str, &str)] = &[
+    ("ui.input", "Input"),
+    ("ui.result", "Result"),
+    ("ui.variables", "Variables"),
+    ("ui.tab.tree", "Tree"),
+    ("ui.tab.ast", "Abstract tree"),
+    ("ui.tab.symbols", "Symbols"),
+    ("ui.tab.tokens", "Tokens"),
+    (
+        "ui.help.input",
+        "Press ESC to exit, TAB to edit variables, F2 to switch view, F3 to show attributes, F4 to change language",
+    ),
+    (
+        "ui.help.variables",
+        "ESC: exit, TAB: input, F2: view, F3: attributes, F4: language, Enter: save 'name = value', ↑/↓: edit, Del: delete",
+    ),
+    ("ui.postfix", "Postfix: {0}"),
+    ("ui.prefix", "Prefix: {0}"),
+    ("ui.value", "Result: {0}"),
+    ("ui.undefined", "No value: {0}"),
+    ("attribute.type", "type"),
+    ("attribute.value", "value"),
+    ("attribute.postfix", "postfix"),
+    ("attribute.span", "pos"),
+    ("attribute.inherited", "inherited"),
+    ("symbols.name", "Name"),
+    ("symbols.kind", "Kind"),
+    ("symbols.type", "Type"),
+    ("symbols.scope", "Scope"),
+    ("symbols.declared", "Declared"),
+    ("symbols.uses", "Uses"),
+    ("tokens.lexeme", "Lexeme"),
+    ("tokens.type", "Type"),
+    ("tokens.location", "Position"),
+    ("symbol.variable", "variable"),
+    ("symbol.parameter", "parameter"),
+    ("symbol.function", "function"),
+    ("symbol.builtin", "builtin"),
+    ("trivia.line_comment", "line comment"),
+    ("trivia.block_comment", "block comment"),
+    ("binding.expected", "Expected: 'name = value'"),
+    ("binding.missing_name", "Expected an identifier before '='"),
+    ("binding.invalid_name", "'{0}' is not a valid identifier"),
+    ("binding.missing_value", "Expected a value after '='"),
+    ("binding.not_numeric", "The value of '{0}' is not numeric"),
+    ("cli.missing_language", "Missing language after '--lang'"),
+    ("cli.unknown_language", "Unknown language '{0}', use 'es' or 'en'"),
+    ("cli.unknown_option", "Unknown option '{0}', usage: analizador-sin [--lang es|en]"),
+    ("token.number", "number"),
+    ("token.id", "identifier"),
+    ("token.eof", "End of file"),
+    ("token.newline", "line break"),
+    ("list.or", "{0} or {1}"),
+    ("list.and", "{0} and {1}"),
+    ("diagnostic.help", "help"),
+    ("error.E0101", "Unexpected character '{0}' at {1}"),
+    ("error.E0102", "Malformed number '{0}' at {1}, expected a digit"),
+    ("error.E0103", "Malformed number '{0}' at {1}, expected an operator"),
+    ("error.E0104", "The comment opened at {1} is never closed"),
+    ("error.E0201", "Unexpected token '{0}' at {1}, expected: {2}"),
+    ("error.E0202", "The parenthesis opened at {1} is never closed"),
+    ("error.E0301", "Undefined function '{0}' at {1}"),
+    (
+        "error.E0302",
+        "Function '{0}' at {1} expects {2} argument(s) but received {3}",
+    ),
+    ("error.E0303", "Duplicate parameter '{0}' at {1}"),
+    (
+        "error.E0304",
+        "Mismatched types: '{0}' at {1} has type {2} but is assigned a {3} value",
+    ),
+    (
+        "error.E0305",
+        "Variable '{0}' at {1} was already declared at {2}",
+    ),
+    (
+        "error.E0306",
+        "Variable '{0}' is used at {1} before its declaration at {2}",
+    ),
+    ("error.E0307", "Variable '{0}' at {1} is used before being initialized"),
+    (
+        "error.E0308",
+        "Invalid operation: '{0}' at {1} does not accept operands of type {2}",
+    ),
+    (
+        "error.E0309",
+        "The branches of '{0}' at {1} have different types: {2} and {3}",
+    ),
+    ("error.E0310", "Numeric overflow in '{0}' at {1}"),
+    ("error.E0311", "Variable with no value '{0}' at {1}"),
+    ("error.E0401", "Division by zero in operator '{0}' at {1}"),
+    (
+        "error.E0402",
+        "The call to '{0}' at {1} exceeds the limit of {2} nested calls",
+    ),
+    (
+        "error.E0402.calls",
+        "The call to '{0}' at {1} exceeds the limit of {2} calls per statement",
+    ),
+    ("hint.unterminated_parenthesis", "Did you forget a closing ')'?"),
+    ("hint.unterminated_comment", "Close the comment with '*/', comments may be nested"),
+    ("hint.unexpected_end", "The input ends before the expression is complete"),
+    ("hint.separator", "Separate statements with ';' or a line break"),
+    ("hint.missing_digit", "Add a digit, for example '{0}0'"),
+    ("hint.undefined_function", "Functions are defined with 'name(parameters) = expression'"),
+    ("hint.undefined_variable", "Assign a value with '{0} = value' or in the variables panel"),
+    ("hint.int_from_float", "Declare the variable as 'float' or use 'div' to divide integers"),
+    ("hint.redeclaration", "To change its value use an assignment: '{0} = value'"),
+    ("hint.use_before_declaration", "Move the declaration before its first use"),
+    ("hint.uninitialized", "Assign a value to '{0}' before using it"),
+    ("hint.branch_mismatch", "Both branches must be bool or both numeric"),
+    ("hint.recursion_limit", "Check that the function has a base case"),
+    ("hint.call_limit", "Each case that calls the function more than once multiplies the calls"),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn keys(language: Language) -> BTreeSet<&'static str> {
+        language.catalog().iter().map(|(key, _)| *key).collect()
+    }
+
+    fn placeholders(message: &str) -> BTreeSet<usize> {
+        (0..10)
+            .filter(|index| message.contains(&format!("{{{}}}", index)))
+            .collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_language() {
+        for language in Language::ALL {
+            for other in Language::ALL {
+                let missing: Vec<_> = keys(language).difference(&keys(other)).copied().collect();
+                assert!(
+                    missing.is_empty(),
+                    "missing in '{}': {:?}",
+                    other.code(),
+                    missing
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn keys_are_not_repeated() {
+        for language in Language::ALL {
+            assert_eq!(keys(language).len(), language.catalog().len());
+        }
+    }
+
+    #[test]
+    fn translations_take_the_same_arguments() {
+        for (key, message) in SPANISH {
+            for other in Language::ALL {
+                let (_, translation) = other
+                    .catalog()
+                    .iter()
+                    .find(|(other_key, _)| other_key == key)
+                    .unwrap();
+                assert_eq!(placeholders(message), placeholders(translation), "{}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn language_from_code_or_locale() {
+        assert_eq!(Language::from_code("en"), Some(Language::English));
+        assert_eq!(Language::from_code("en_US.UTF-8"), Some(Language::English));
+        assert_eq!(Language::from_code("es_AR.UTF-8"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("C"), None);
+    }
+}
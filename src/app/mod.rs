@@ -1,22 +1,168 @@
-use self::analyzer::sintactic::{Analyzed, Analyzer, AnalyzerResult};
+use self::{
+    analyzer::{
+        lexic::{LexicAnalyzer, TokenType},
+        sintactic::{Analyzed, Analyzer, Environment},
+        types::Value,
+    },
+    i18n::{set_language, text, tr, Language},
+};
 
 pub mod analyzer;
+pub mod i18n;
 pub mod tree;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Focus {
+    Input,
+    Variables,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tab {
+    Tree,
+    Ast,
+    Symbols,
+    Tokens,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Tree, Tab::Ast, Tab::Symbols, Tab::Tokens];
+}
+
 pub struct App {
     pub input: String,
-    last_result: AnalyzerResult,
+    pub focus: Focus,
+    pub tab: Tab,
+    pub show_attributes: bool,
+    pub language: Language,
+    pub variables: Environment,
+    pub variable_input: String,
+    pub variable_error: Option<String>,
+    pub selected_variable: Option<usize>,
+    last_result: Analyzed,
     last_input: String,
+    last_variables: Environment,
+    last_language: Language,
 }
 
 impl App {
-    pub fn run_analyzer(&mut self) -> AnalyzerResult {
-        if self.last_input == self.input {
-            return self.last_result.clone();
+    /// Analyzes the input unless it was analyzed with the same variables and
+    /// language, the result is kept for `analyzed` instead of copied every
+    /// frame
+    pub fn run_analyzer(&mut self) {
+        // Token names in the tree and the messages depend on the language
+        if self.last_input == self.input
+            && self.last_variables == self.variables
+            && self.last_language == self.language
+        {
+            return;
         }
-        let mut analyzer = Analyzer::new(&self.input);
+        let mut analyzer = Analyzer::new(&self.input, &self.variables);
         self.last_result = analyzer.analyze();
         self.last_input = self.input.clone();
-        return self.last_result.clone();
+        self.last_variables = self.variables.clone();
+        self.last_language = self.language;
+    }
+
+    /// Result of the last `run_analyzer`
+    pub fn analyzed(&self) -> &Analyzed {
+        &self.last_result
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Input => Focus::Variables,
+            Focus::Variables => Focus::Input,
+        };
+    }
+
+    pub fn next_tab(&mut self) {
+        let index = Tab::ALL
+            .iter()
+            .position(|tab| *tab == self.tab)
+            .unwrap_or(0);
+        self.tab = Tab::ALL[(index + 1) % Tab::ALL.len()];
+    }
+
+    pub fn toggle_attributes(&mut self) {
+        self.show_attributes = !self.show_attributes;
+    }
+
+    pub fn next_language(&mut self) {
+        self.language = self.language.next();
+        set_language(self.language);
+        self.variable_error = None;
+    }
+
+    pub fn commit_variable(&mut self) {
+        match self.parse_binding(&self.variable_input) {
+            Ok((name, value)) => {
+                self.variables.insert(name.clone(), value);
+                self.selected_variable = self.variables.keys().position(|key| *key == name);
+                self.variable_input.clear();
+                self.variable_error = None;
+            }
+            Err(err) => self.variable_error = Some(err),
+        }
+    }
+
+    pub fn select_variable(&mut self, offset: isize) {
+        if self.variables.is_empty() {
+            self.selected_variable = None;
+            return;
+        }
+        let last = self.variables.len() as isize - 1;
+        let index = match self.selected_variable {
+            Some(index) => (index as isize + offset).clamp(0, last),
+            None if offset < 0 => last,
+            None => 0,
+        } as usize;
+        self.selected_variable = Some(index);
+        if let Some((name, value)) = self.variables.iter().nth(index) {
+            self.variable_input = format!("{} = {}", name, value);
+        }
+        self.variable_error = None;
+    }
+
+    pub fn delete_selected_variable(&mut self) {
+        let name = match self.selected_variable {
+            Some(index) => self.variables.keys().nth(index).cloned(),
+            None => None,
+        };
+        if let Some(name) = name {
+            self.variables.remove(&name);
+            self.variable_input.clear();
+            self.variable_error = None;
+            self.selected_variable = match self.variables.len() {
+                0 => None,
+                len => self.selected_variable.map(|index| index.min(len - 1)),
+            };
+        }
+    }
+
+    fn parse_binding(&self, binding: &str) -> Result<(String, Value), String> {
+        let (name, value) = match binding.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => return Err(text("binding.expected").to_string()),
+        };
+        if name.is_empty() {
+            return Err(text("binding.missing_name").to_string());
+        }
+        let token = LexicAnalyzer::new(name).consume_token();
+        if token.token_type != TokenType::Id || token.lexeme != name {
+            return Err(tr("binding.invalid_name", &[&name]));
+        }
+        if value.is_empty() {
+            return Err(text("binding.missing_value").to_string());
+        }
+        let analyzed = Analyzer::new(value, &self.variables).analyze();
+        if let Some(err) = analyzed.errors.first() {
+            return Err(format!("{}", err));
+        }
+        match analyzed.result {
+            Some(Value::Bool(_)) | None => Err(tr("binding.not_numeric", &[&name])),
+            Some(num) => Ok((String::from(name), num)),
+        }
     }
 }
 
@@ -24,8 +170,44 @@ impl Default for App {
     fn default() -> Self {
         App {
             input: String::from(""),
+            focus: Focus::Input,
+            tab: Tab::Tree,
+            show_attributes: false,
+            language: i18n::language(),
+            variables: Environment::new(),
+            variable_input: String::from(""),
+            variable_error: None,
+            selected_variable: None,
             last_input: String::from(""),
-            last_result: Ok(Analyzed::default()),
+            last_result: Analyzed::default(),
+            last_variables: Environment::new(),
+            last_language: i18n::language(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_take_numeric_values_only() {
+        let app = App::default();
+        assert_eq!(
+            app.parse_binding("x = 2 * 3"),
+            Ok((String::from("x"), Value::Int(6)))
+        );
+        assert_eq!(
+            app.parse_binding("x = 1 / 2"),
+            Ok((String::from("x"), Value::Float(0.5)))
+        );
+        for binding in ["x = 1 < 2", "x = true", "x = y"] {
+            assert_eq!(
+                app.parse_binding(binding),
+                Err(tr("binding.not_numeric", &[&"x"])),
+                "{:?}",
+                binding
+            );
         }
     }
 }
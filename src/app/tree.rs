@@ -1,7 +1,24 @@
+use super::analyzer::{
+    lexic::Span,
+    types::{Type, Value},
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Attributes {
+    pub node_type: Option<Type>,
+    pub value: Option<Value>,
+    /// Byte range of the node in the postfix notation of the whole program,
+    /// each node would otherwise copy the notation of its subtree
+    pub postfix: Option<(usize, usize)>,
+    pub span: Option<Span>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct TreeItem {
     pub root: String,
     pub items: Vec<TreeItem>,
+    pub attributes: Attributes,
+    pub inherited: Option<Attributes>,
 }
 
 impl TreeItem {
@@ -9,6 +26,20 @@ impl TreeItem {
         TreeItem {
             root: String::from(root),
             items: Vec::new(),
+            attributes: Attributes::default(),
+            inherited: None,
+        }
+    }
+}
+
+/// Dropped from a list of the nodes left to drop, the `rest` nodes of a chain
+/// nest as deep as the chain is long and dropping each in its parent would
+/// take a stack frame per operator
+impl Drop for TreeItem {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.items);
+        while let Some(mut item) = pending.pop() {
+            pending.append(&mut item.items);
         }
     }
 }
@@ -0,0 +1,68 @@
+use std::{
+    env,
+    io::{self, Read},
+};
+
+use crate::app::{
+    analyzer::{diagnostic::render, sintactic::Analyzer},
+    i18n::{tr, Language},
+};
+
+/// Non interactive mode: analyzes the input read from stdin and prints the
+/// same results as the "Resultado" panel, or the diagnostics of every error.
+/// Warnings are printed to stderr before the results
+/// Returns whether the input had no errors
+pub fn run() -> io::Result<bool> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    let input = input.trim_end();
+    let res = Analyzer::new(input, &Default::default()).analyze();
+    if !res.errors.is_empty() {
+        eprintln!("{}", render(input, &res.errors));
+        return Ok(false);
+    }
+    if !res.warnings.is_empty() {
+        eprintln!("{}", render(input, &res.warnings));
+    }
+    for line in res.postfix.lines() {
+        println!("{}", tr("ui.postfix", &[&line]));
+    }
+    for line in res.prefix.lines() {
+        println!("{}", tr("ui.prefix", &[&line]));
+    }
+    if let Some(num) = res.result {
+        println!("{}", tr("ui.value", &[&num]));
+    } else if !res.undefined.is_empty() {
+        println!("{}", tr("ui.undefined", &[&res.undefined.join(", ")]));
+    }
+    Ok(true)
+}
+
+/// Language of the `LANG` environment variable, e.g. `en_US.UTF-8`
+pub fn language_from_env() -> Option<Language> {
+    env::var("LANG")
+        .ok()
+        .and_then(|lang| Language::from_code(&lang))
+}
+
+/// Language chosen by `--lang <code>` or `--lang=<code>`, the last one wins
+pub fn language_from_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<Language>, String> {
+    let mut selected = None;
+    while let Some(arg) = args.next() {
+        let code = match arg.strip_prefix("--lang") {
+            Some("") => match args.next() {
+                Some(code) => code,
+                None => return Err(tr("cli.missing_language", &[])),
+            },
+            Some(code) if code.starts_with('=') => code[1..].to_string(),
+            _ => return Err(tr("cli.unknown_option", &[&arg])),
+        };
+        selected = match Language::from_code(&code) {
+            Some(language) => Some(language),
+            None => return Err(tr("cli.unknown_language", &[&code])),
+        };
+    }
+    Ok(selected)
+}
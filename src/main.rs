@@ -1,11 +1,35 @@
-use std::error;
+use std::{
+    env, error,
+    io::{self, IsTerminal},
+    process,
+};
 
-use ui::{restore_terminal, run_app, setup_terminal};
-
-pub mod app;
-pub mod ui;
+use analizador_sin::{
+    app::{self, i18n::set_language},
+    cli,
+    ui::{restore_terminal, run_app, setup_terminal},
+};
 
 fn main() -> Result<(), Box<dyn error::Error>> {
+    // The flag takes precedence over `LANG`
+    if let Some(language) = cli::language_from_env() {
+        set_language(language);
+    }
+    match cli::language_from_args(env::args().skip(1)) {
+        Ok(Some(language)) => set_language(language),
+        Ok(None) => (),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+    // Piped input is analyzed without the interface, e.g. `echo "1 + 2" | analizador-sin`
+    if !io::stdin().is_terminal() {
+        if !cli::run()? {
+            process::exit(1);
+        }
+        return Ok(());
+    }
     let mut terminal = setup_terminal()?;
     let app = app::App::default();
     run_app(&mut terminal, app)?;
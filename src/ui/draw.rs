@@ -1,105 +1,429 @@
 use tui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Style},
-    text::Spans,
-    widgets::{Block, Borders, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, Tabs},
     Frame,
 };
 
-use crate::app::{tree::TreeItem, App};
+use crate::app::{
+    analyzer::{
+        diagnostic::{Diagnostic, Part},
+        error::AnalyzerError,
+        lexic::{tokenize, TriviaKind},
+        semantic::{SymbolKind, SymbolTable},
+    },
+    i18n::{text, tr},
+    tree::{Attributes, TreeItem},
+    App, Focus, Tab,
+};
+
+const MAX_INPUT_LINES: usize = 8;
+const MAX_RESULT_LINES: usize = 8;
+const MAX_ERROR_LINES: usize = 16;
 
 pub fn draw_frame<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    let err_str: String;
+    let result = if app.input.is_empty() {
+        None
+    } else {
+        app.run_analyzer();
+        Some(app.analyzed())
+    };
+    let diagnostics: Vec<Spans> = match &result {
+        Some(res) => res
+            .errors
+            .iter()
+            .flat_map(|err| Diagnostic::new(&app.input, err).lines())
+            .map(|(part, line)| Spans::from(Span::styled(line, diagnostic_style(part))))
+            .collect(),
+        None => Vec::new(),
+    };
+    let input_height = app.input.split('\n').count().min(MAX_INPUT_LINES) as u16 + 2;
+    let result_height = match &result {
+        Some(res) if !res.errors.is_empty() => diagnostics.len().min(MAX_ERROR_LINES) as u16 + 2,
+        Some(res) => res.postfix.lines().count().clamp(1, MAX_RESULT_LINES) as u16 + 2,
+        None => 3,
+    };
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints(
             [
-                Constraint::Length(3),
-                Constraint::Length(3),
+                Constraint::Length(input_height),
+                Constraint::Length(result_height),
                 Constraint::Min(5),
                 Constraint::Length(1),
             ]
             .as_ref(),
         )
         .split(f.size());
+    let body_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(20), Constraint::Length(30)].as_ref())
+        .split(main_layout[2]);
+    let view_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+        .split(body_layout[0]);
     let input = Paragraph::new(app.input.as_ref()).block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::LightCyan))
-            .title("Entrada"),
+            .border_style(focus_style(app, Focus::Input))
+            .title(text("ui.input")),
     );
 
     f.render_widget(input, main_layout[0]);
+    draw_variables(f, app, body_layout[1]);
+
+    let titles = Tab::ALL
+        .iter()
+        .map(|tab| Spans::from(tab_title(*tab)))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(Tab::ALL.iter().position(|tab| *tab == app.tab).unwrap_or(0))
+        .highlight_style(Style::default().fg(Color::LightCyan));
+    f.render_widget(tabs, view_layout[0]);
 
-    let instructions =
-        Paragraph::new("Presione ESC para salir").style(Style::default().fg(Color::LightCyan));
+    let instructions = match app.focus {
+        Focus::Input => text("ui.help.input"),
+        Focus::Variables => text("ui.help.variables"),
+    };
+    let instructions = Paragraph::new(instructions).style(Style::default().fg(Color::LightCyan));
     f.render_widget(instructions, main_layout[3]);
 
-    let mut result_block = Block::default().borders(Borders::ALL).title("Resultado");
-    let tree_block = Block::default().borders(Borders::ALL).title("Árbol");
-
-    if app.input.len() > 0 {
-        let result = app.run_analyzer();
-        match result {
-            Ok(res) => {
-                let result_layout = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .margin(1)
-                    .constraints(
-                        [
-                            Constraint::Percentage(33),
-                            Constraint::Percentage(33),
-                            Constraint::Percentage(33),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(main_layout[1]);
-                let posfix_result = Paragraph::new(format!("Posfijo: {}", res.postfix))
-                    .alignment(Alignment::Center);
-                let prefix_result =
-                    Paragraph::new(format!("Prefijo: {}", res.prefix)).alignment(Alignment::Center);
-                let tree_paragraph = get_tree_paragraph(&res.tree).block(tree_block);
-                f.render_widget(result_block, main_layout[1]);
-                f.render_widget(posfix_result, result_layout[0]);
-                f.render_widget(prefix_result, result_layout[1]);
-                f.render_widget(tree_paragraph, main_layout[2]);
-                if let Some(num) = res.result {
-                    let numeric_result = Paragraph::new(format!("Resultado: {}", num));
-                    f.render_widget(numeric_result, result_layout[2]);
-                }
+    let mut result_block = Block::default()
+        .borders(Borders::ALL)
+        .title(text("ui.result"));
+    let tree_block = Block::default()
+        .borders(Borders::ALL)
+        .title(tab_title(app.tab));
+
+    if let Some(res) = result {
+        // The recovered tree is shown even when there are errors
+        match app.tab {
+            Tab::Tree => {
+                let tree_paragraph = get_tree_paragraph(
+                    &res.tree,
+                    &app.input,
+                    &res.postfix,
+                    app.show_attributes,
+                    view_layout[1].height,
+                )
+                .block(tree_block);
+                f.render_widget(tree_paragraph, view_layout[1]);
             }
-            Err(err) => {
-                err_str = format!("{}", err);
-                result_block = result_block.border_style(Style::default().fg(Color::LightRed));
-                let error_paragraph = Paragraph::new(err_str.as_ref()).block(result_block);
-                f.render_widget(error_paragraph, main_layout[1]);
-                f.render_widget(tree_block, main_layout[2])
+            Tab::Ast => {
+                let ast = res.ast.tree();
+                let ast_paragraph = get_tree_paragraph(
+                    &ast,
+                    &app.input,
+                    &res.postfix,
+                    app.show_attributes,
+                    view_layout[1].height,
+                )
+                .block(tree_block);
+                f.render_widget(ast_paragraph, view_layout[1]);
             }
-        };
-        return ();
+            Tab::Symbols => {
+                let symbols_table = get_symbols_table(&res.symbols).block(tree_block);
+                f.render_widget(symbols_table, view_layout[1]);
+            }
+            Tab::Tokens => {
+                let tokens_table = get_tokens_table(&app.input, &res.errors).block(tree_block);
+                f.render_widget(tokens_table, view_layout[1]);
+            }
+        }
+        if !res.errors.is_empty() {
+            result_block = result_block.border_style(Style::default().fg(Color::LightRed));
+            let error_paragraph = Paragraph::new(diagnostics).block(result_block);
+            f.render_widget(error_paragraph, main_layout[1]);
+            return;
+        }
+        let result_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Percentage(33),
+                    Constraint::Percentage(33),
+                    Constraint::Percentage(33),
+                ]
+                .as_ref(),
+            )
+            .split(main_layout[1]);
+        let posfix_result =
+            Paragraph::new(labeled_lines("ui.postfix", &res.postfix)).alignment(Alignment::Center);
+        let prefix_result =
+            Paragraph::new(labeled_lines("ui.prefix", &res.prefix)).alignment(Alignment::Center);
+        f.render_widget(result_block, main_layout[1]);
+        f.render_widget(posfix_result, result_layout[0]);
+        f.render_widget(prefix_result, result_layout[1]);
+        if let Some(num) = res.result {
+            let numeric_result = Paragraph::new(tr("ui.value", &[&num]));
+            f.render_widget(numeric_result, result_layout[2]);
+        } else if !res.undefined.is_empty() {
+            let undefined_result = Paragraph::new(tr("ui.undefined", &[&res.undefined.join(", ")]))
+                .style(Style::default().fg(Color::LightYellow));
+            f.render_widget(undefined_result, result_layout[2]);
+        }
+        return;
     }
     f.render_widget(result_block, main_layout[1]);
-    f.render_widget(tree_block, main_layout[2])
+    f.render_widget(tree_block, view_layout[1])
+}
+
+fn tab_title(tab: Tab) -> &'static str {
+    match tab {
+        Tab::Tree => text("ui.tab.tree"),
+        Tab::Ast => text("ui.tab.ast"),
+        Tab::Symbols => text("ui.tab.symbols"),
+        Tab::Tokens => text("ui.tab.tokens"),
+    }
+}
+
+fn labeled_lines<'a>(key: &str, lines: &str) -> Vec<Spans<'a>> {
+    lines
+        .lines()
+        .map(|line| Spans::from(tr(key, &[&line])))
+        .collect()
+}
+
+fn diagnostic_style(part: Part) -> Style {
+    match part {
+        Part::Message => Style::default()
+            .fg(Color::LightRed)
+            .add_modifier(Modifier::BOLD),
+        Part::Source => Style::default(),
+        Part::Underline => Style::default().fg(Color::LightRed),
+        Part::Hint => Style::default().fg(Color::LightCyan),
+    }
+}
+
+fn focus_style(app: &App, focus: Focus) -> Style {
+    if app.focus == focus {
+        return Style::default().fg(Color::LightCyan);
+    }
+    Style::default()
+}
+
+fn draw_variables<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(focus_style(app, Focus::Variables))
+        .title(text("ui.variables"));
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(block.inner(area));
+    f.render_widget(block, area);
+
+    let items: Vec<ListItem> = app
+        .variables
+        .iter()
+        .map(|(name, value)| ListItem::new(format!("{} = {}", name, value)))
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    state.select(app.selected_variable);
+    f.render_stateful_widget(list, layout[0], &mut state);
+
+    let variable_input = Paragraph::new(format!("> {}", app.variable_input));
+    f.render_widget(variable_input, layout[1]);
+    if let Some(err) = &app.variable_error {
+        let error_paragraph =
+            Paragraph::new(err.as_ref()).style(Style::default().fg(Color::LightRed));
+        f.render_widget(error_paragraph, layout[2]);
+    }
 }
 
-fn get_tree_spans<'a>(tree: &'a TreeItem, prepend: &str) -> Vec<Spans<'a>> {
-    let mut span_str: String = format!("{}├ {}", prepend, tree.root);
-    let mut next_prepend = prepend.to_owned() + "│  ";
-    if prepend.len() == 0 {
-        span_str = format!("{}", tree.root);
-        next_prepend = prepend.to_owned() + " ";
+/// Attributes of a node, the postfix notation is sliced out of the one of the
+/// whole program
+fn format_attributes(attributes: &Attributes, input: &str, postfix: &str) -> String {
+    let mut fields = Vec::new();
+    if let Some(node_type) = attributes.node_type {
+        fields.push(format!("{}={}", text("attribute.type"), node_type));
     }
-    let mut res = vec![Spans::from(span_str)];
-    for item in tree.items.iter() {
-        for span in get_tree_spans(&item, &next_prepend) {
-            res.push(span);
+    if let Some(value) = attributes.value {
+        fields.push(format!("{}={}", text("attribute.value"), value));
+    }
+    let notation = attributes
+        .postfix
+        .and_then(|(start, end)| postfix.get(start..end));
+    if let Some(postfix) = notation {
+        fields.push(format!(
+            "{}=\"{}\"",
+            text("attribute.postfix"),
+            postfix.replace('\n', "; ")
+        ));
+    }
+    if let Some((start, end)) = attributes.span {
+        // Spans are byte offsets, positions are shown counting characters
+        let position = |offset: usize| input[..offset.min(input.len())].chars().count();
+        fields.push(format!(
+            "{}={}-{}",
+            text("attribute.span"),
+            position(start) + 1,
+            position(end)
+        ));
+    }
+    fields.join(", ")
+}
+
+/// Lines of the tree down to the `height` of the view, the nodes below it are
+/// not formatted. Walked from a stack of the nodes left with their depth, the
+/// `rest` nodes of a chain nest as deep as the chain is long
+fn get_tree_spans<'a>(
+    tree: &'a TreeItem,
+    input: &str,
+    postfix: &str,
+    attributes: bool,
+    height: u16,
+) -> Vec<Spans<'a>> {
+    let mut res = Vec::new();
+    let mut pending = vec![(tree, 0)];
+    while let Some((tree, depth)) = pending.pop() {
+        if res.len() >= height as usize {
+            break;
+        }
+        let mut root = match tree.attributes.node_type {
+            Some(node_type) if !attributes => format!("{}: {}", tree.root, node_type),
+            _ => tree.root.to_string(),
+        };
+        if attributes {
+            let synthesized = format_attributes(&tree.attributes, input, postfix);
+            if !synthesized.is_empty() {
+                root = format!("{} [{}]", root, synthesized);
+            }
+            if let Some(inherited) = &tree.inherited {
+                root = format!(
+                    "{} ({}: {})",
+                    root,
+                    text("attribute.inherited"),
+                    format_attributes(inherited, input, postfix)
+                );
+            }
         }
+        if depth > 0 {
+            root = format!(" {}├ {}", "│  ".repeat(depth - 1), root);
+        }
+        res.push(Spans::from(root));
+        pending.extend(tree.items.iter().rev().map(|item| (item, depth + 1)));
     }
     res
 }
 
-fn get_tree_paragraph<'a>(tree: &'a TreeItem) -> Paragraph {
-    Paragraph::new(get_tree_spans(tree, ""))
+fn get_tree_paragraph<'a>(
+    tree: &'a TreeItem,
+    input: &str,
+    postfix: &str,
+    attributes: bool,
+    height: u16,
+) -> Paragraph<'a> {
+    Paragraph::new(get_tree_spans(tree, input, postfix, attributes, height))
+}
+
+fn get_symbols_table(symbols: &SymbolTable) -> Table<'_> {
+    let header = Row::new(
+        [
+            "symbols.name",
+            "symbols.kind",
+            "symbols.type",
+            "symbols.scope",
+            "symbols.declared",
+            "symbols.uses",
+        ]
+        .map(text),
+    )
+    .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = symbols.symbols().iter().map(|symbol| {
+        let kind = match symbol.kind {
+            SymbolKind::Variable => text("symbol.variable"),
+            SymbolKind::Parameter => text("symbol.parameter"),
+            SymbolKind::Function => text("symbol.function"),
+            SymbolKind::Builtin => text("symbol.builtin"),
+        };
+        let declared_at = match symbol.declared_at {
+            Some(pos) => pos.to_string(),
+            None => String::from("-"),
+        };
+        let used_at: Vec<String> = symbol.used_at.iter().map(|pos| pos.to_string()).collect();
+        Row::new(vec![
+            Cell::from(symbol.name.clone()),
+            Cell::from(kind),
+            Cell::from(symbol.symbol_type.to_string()),
+            Cell::from(symbols.scope_name(symbol.scope).to_string()),
+            Cell::from(declared_at),
+            Cell::from(used_at.join(", ")),
+        ])
+    });
+    Table::new(rows).header(header).widths(&[
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Min(10),
+    ])
+}
+
+/// Tokens of the input, the ones where an error starts are highlighted.
+/// Lexical errors are shown in place of the characters they skip and comments
+/// before the token they are attached to
+fn get_tokens_table<'a>(input: &str, errors: &[AnalyzerError]) -> Table<'a> {
+    let header = Row::new(["tokens.lexeme", "tokens.type", "tokens.location"].map(text))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let error_style = Style::default()
+        .fg(Color::LightRed)
+        .add_modifier(Modifier::BOLD);
+    let mut rows = Vec::new();
+    for token in tokenize(input) {
+        let token = match token {
+            Ok(token) => token,
+            Err(error) => {
+                let row = Row::new(vec![
+                    error.lexeme().to_string(),
+                    format!("error[{}]", error.code()),
+                    error.location().to_string(),
+                ]);
+                rows.push(row.style(error_style));
+                continue;
+            }
+        };
+        for trivia in token.trivia.iter() {
+            let kind = match trivia.kind {
+                TriviaKind::LineComment => text("trivia.line_comment"),
+                TriviaKind::BlockComment => text("trivia.block_comment"),
+            };
+            let row = Row::new(vec![
+                trivia.text.replace('\n', " "),
+                kind.to_string(),
+                trivia.location.to_string(),
+            ]);
+            rows.push(row.style(Style::default().fg(Color::DarkGray)));
+        }
+        // A line break that ends a statement is a `;` token
+        let row = Row::new(vec![
+            token.lexeme.replace('\n', "⏎"),
+            format!("{:?}", token.token_type),
+            token.location.to_string(),
+        ]);
+        if errors.iter().any(|error| error.position() == token.span.0) {
+            rows.push(row.style(error_style));
+        } else {
+            rows.push(row);
+        }
+    }
+    Table::new(rows).header(header).widths(&[
+        Constraint::Length(16),
+        Constraint::Length(20),
+        Constraint::Min(10),
+    ])
 }
@@ -10,7 +10,7 @@ use tui::{
     Terminal,
 };
 
-use crate::app::App;
+use crate::app::{App, Focus};
 
 use self::draw::draw_frame;
 
@@ -20,17 +20,34 @@ pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Resu
     loop {
         terminal.draw(|f| draw_frame(f, &mut app))?;
         if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Esc => {
+            match (key.code, app.focus) {
+                (KeyCode::Esc, _) => {
                     return Ok(());
                 }
-                KeyCode::Char(c) => {
+                (KeyCode::Tab, _) => app.toggle_focus(),
+                (KeyCode::F(2), _) => app.next_tab(),
+                (KeyCode::F(3), _) => app.toggle_attributes(),
+                (KeyCode::F(4), _) => app.next_language(),
+                (KeyCode::Char(c), Focus::Input) => {
                     app.input.push(c);
                 }
-                KeyCode::Backspace =>{
+                (KeyCode::Backspace, Focus::Input) => {
                     app.input.pop();
                 }
-                _ => ()
+                (KeyCode::Enter, Focus::Input) => {
+                    app.input.push('\n');
+                }
+                (KeyCode::Char(c), Focus::Variables) => {
+                    app.variable_input.push(c);
+                }
+                (KeyCode::Backspace, Focus::Variables) => {
+                    app.variable_input.pop();
+                }
+                (KeyCode::Enter, Focus::Variables) => app.commit_variable(),
+                (KeyCode::Up, Focus::Variables) => app.select_variable(-1),
+                (KeyCode::Down, Focus::Variables) => app.select_variable(1),
+                (KeyCode::Delete, Focus::Variables) => app.delete_selected_variable(),
+                _ => (),
             }
         }
     }